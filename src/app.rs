@@ -1 +1,333 @@
+use std::time::Duration;
 
+use termion::event::{Event, Key};
+
+use character::{CharacterForm, FormStatus};
+use command::{Command, RoomTarget};
+use config::PanelSizes;
+use game::{Entity, GameInformation};
+use input::InputLine;
+use keys::KeyBindings;
+use net::Status;
+use protocol::{ServerMessage, CHARACTER_TYPE};
+use theme::{self, Theme};
+use travel::{Journey, Progress};
+
+const PASTE_START: &[u8] = b"\x1b[200~";
+const PASTE_END: &[u8] = b"\x1b[201~";
+
+const CHARACTER_REPLY_TIMEOUT_SECS: u64 = 10;
+
+pub enum Screen {
+    CreateCharacter(CharacterForm),
+    Main,
+}
+
+/// The panel that arrow keys currently act on.
+#[derive(Clone, Copy, PartialEq)]
+pub enum Focus {
+    Input,
+    Rooms,
+    Entities,
+}
+
+/// What the top info section shows.
+#[derive(Clone, Copy, PartialEq)]
+pub enum InfoTab {
+    Room,
+    Combat,
+    Map,
+}
+
+/// Which messages the feed shows.
+#[derive(Clone, PartialEq)]
+pub enum FeedView {
+    All,
+    /// Only messages between the player and the named correspondent.
+    Conversation(String),
+}
+
+/// Geometry of the message feed as last drawn, needed to page through it.
+#[derive(Clone, Copy, Default)]
+pub struct FeedLayout {
+    pub page_height: usize,
+    pub max_scroll: usize,
+}
+
+/// Something the user asked for that reaches beyond the UI.
+pub enum Action {
+    Submit(String),
+    Execute(Command),
+    SendCharacter(Entity),
+    Notice(String),
+}
+
+/// Client side state that isn't part of the game itself.
+pub struct App {
+    pub screen: Screen,
+    pub input: InputLine,
+    pub focus: Focus,
+    pub selected_room: usize,
+    /// Row of the map tab's room tree that's highlighted.
+    pub selected_map_line: usize,
+    pub selected_entity: usize,
+    /// Lines scrolled back from the newest message, zero follows the feed.
+    pub feed_scroll: usize,
+    pub feed_layout: FeedLayout,
+    pub feed_view: FeedView,
+    pub info_tab: InfoTab,
+    /// The `/travel` underway, if any.
+    pub travel: Option<Journey>,
+    /// The preset `theme` was last set from.
+    pub theme_name: String,
+    pub theme: Theme,
+    pub panels: PanelSizes,
+    pub keys: KeyBindings,
+    pub connection_status: Status,
+    /// The character last sent to the server, sent again after reconnecting.
+    pub character: Option<Entity>,
+    /// Whether START has been sent, so it can be sent again after
+    /// reconnecting.
+    pub started: bool,
+    /// Set by `/reload` for whoever owns the scripts.
+    pub reload_scripts: bool,
+    pub should_quit: bool,
+    pasting: bool,
+}
+
+impl App {
+    pub fn new(screen: Screen) -> App {
+        App {
+            screen,
+            input: InputLine::default(),
+            focus: Focus::Input,
+            selected_room: 0,
+            selected_map_line: 0,
+            selected_entity: 0,
+            feed_scroll: 0,
+            feed_layout: FeedLayout::default(),
+            feed_view: FeedView::All,
+            info_tab: InfoTab::Room,
+            travel: None,
+            theme_name: String::from(theme::PRESETS[0]),
+            theme: Theme::default(),
+            panels: PanelSizes::default(),
+            keys: KeyBindings::default(),
+            connection_status: Status::Offline,
+            character: None,
+            started: false,
+            reload_scripts: false,
+            should_quit: false,
+            pasting: false,
+        }
+    }
+
+    /// Applies a terminal event to whichever screen is showing.
+    pub fn handle_event(&mut self, evt: Event, game_info: &GameInformation) -> Option<Action> {
+        match evt {
+            Event::Key(key) if key == self.keys.quit => {
+                self.should_quit = true;
+                None
+            }
+            Event::Key(key) => match self.screen {
+                Screen::CreateCharacter(ref mut form) => {
+                    form.handle_key(key, &game_info.game).map(Action::SendCharacter)
+                }
+                Screen::Main => self.handle_key(key, game_info),
+            },
+            Event::Unsupported(ref bytes) if bytes.as_slice() == PASTE_START => {
+                self.pasting = true;
+                None
+            }
+            Event::Unsupported(ref bytes) if bytes.as_slice() == PASTE_END => {
+                self.pasting = false;
+                None
+            }
+            _ => None,
+        }
+    }
+
+    fn waiting_on_character(&self) -> bool {
+        matches!(
+            self.screen,
+            Screen::CreateCharacter(CharacterForm {
+                status: FormStatus::Waiting(_),
+                ..
+            })
+        )
+    }
+
+    pub fn character_rejected(&mut self, reason: String) {
+        if let Screen::CreateCharacter(ref mut form) = self.screen {
+            form.status = FormStatus::Rejected(reason);
+        }
+    }
+
+    /// Picks out the replies the UI is waiting on. The packet has already
+    /// been applied to `game_info` by the reader.
+    pub fn handle_server_message(
+        &mut self,
+        msg: &ServerMessage,
+        game_info: &mut GameInformation,
+    ) -> Option<Action> {
+        if self.waiting_on_character() {
+            match *msg {
+                ServerMessage::Accept(CHARACTER_TYPE) => {
+                    self.screen = Screen::Main;
+                    game_info
+                        .notice(String::from("Character accepted. Type /start to enter the game."));
+                }
+                ServerMessage::Error { ref message, .. } => {
+                    self.character_rejected(message.clone())
+                }
+                _ => {}
+            }
+            return None;
+        }
+
+        let progress = match self.travel {
+            Some(ref mut journey) => journey.advance(msg, game_info),
+            None => return None,
+        };
+        match progress {
+            Progress::Waiting => None,
+            Progress::Step(next) => Some(Action::Execute(Command::Go(RoomTarget::Number(next)))),
+            Progress::Arrived => {
+                if let Some(journey) = self.travel.take() {
+                    game_info.notice(format!("Arrived at {}.", journey.destination));
+                }
+                None
+            }
+            Progress::Stopped(reason) => {
+                self.travel = None;
+                game_info.notice(format!("Travel stopped: {}", reason));
+                None
+            }
+        }
+    }
+
+    /// Called on every clock tick to expire anything that has waited too long.
+    pub fn tick(&mut self) {
+        if let Screen::CreateCharacter(ref mut form) = self.screen {
+            let expired = match form.status {
+                FormStatus::Waiting(sent) => {
+                    sent.elapsed() > Duration::from_secs(CHARACTER_REPLY_TIMEOUT_SECS)
+                }
+                _ => false,
+            };
+            if expired {
+                form.status = FormStatus::Rejected(String::from("The server did not respond."));
+            }
+        }
+    }
+
+    /// Highlights an exit picked with the mouse.
+    pub fn select_room(&mut self, index: usize, game_info: &GameInformation) {
+        if index < game_info.adjacent_rooms.len() {
+            self.selected_room = index;
+            self.focus = Focus::Rooms;
+        }
+    }
+
+    /// Switches to a preset theme, replacing any colors from the config file.
+    pub fn set_theme(&mut self, name: &str) -> Result<(), String> {
+        self.theme = Theme::preset(name).ok_or_else(|| theme::unknown_preset(name))?;
+        self.theme_name = name.to_lowercase();
+        Ok(())
+    }
+
+    fn cycle_focus(&mut self) {
+        self.focus = match self.focus {
+            Focus::Input => Focus::Rooms,
+            Focus::Rooms => Focus::Entities,
+            Focus::Entities => Focus::Input,
+        };
+    }
+
+    fn handle_key(&mut self, key: Key, game_info: &GameInformation) -> Option<Action> {
+        let room_count = game_info.adjacent_rooms.len();
+        let on_map = self.focus == Focus::Rooms && self.info_tab == InfoTab::Map;
+        let entity_count = game_info.current_enemies.len();
+
+        match key {
+            // Pasted text may span lines, keep it on the one input line.
+            Key::Char('\n') if self.pasting => self.input.insert(' '),
+            Key::Char('\n') if on_map => {
+                let lines = game_info.map.tree(game_info.current_room.number);
+                if let Some(line) = lines.get(self.selected_map_line) {
+                    let target = RoomTarget::Number(line.number);
+                    return Some(Action::Execute(Command::Travel(target)));
+                }
+            }
+            Key::Char('\n') if self.focus == Focus::Rooms => {
+                if let Some(room) = game_info.adjacent_rooms.get(self.selected_room) {
+                    return Some(Action::Execute(Command::Go(RoomTarget::Number(room.number))));
+                }
+            }
+            Key::Char('\n') if !self.input.is_empty() => {
+                return Some(Action::Submit(self.input.submit()));
+            }
+            Key::Char('\n') => {}
+            Key::Char('\t') if self.pasting => self.input.insert(' '),
+            key if key == self.keys.cycle_focus => self.cycle_focus(),
+            key if key == self.keys.stop_travel => {
+                if let Some(journey) = self.travel.take() {
+                    return Some(Action::Notice(format!(
+                        "Stopped travelling to {}.",
+                        journey.destination
+                    )));
+                }
+            }
+            key if key == self.keys.next_theme => {
+                let name = theme::next_preset(&self.theme_name);
+                if self.set_theme(name).is_ok() {
+                    return Some(Action::Notice(format!("Theme: {}", name)));
+                }
+            }
+            key if key == self.keys.room_tab => self.info_tab = InfoTab::Room,
+            key if key == self.keys.combat_tab => self.info_tab = InfoTab::Combat,
+            key if key == self.keys.map_tab => self.info_tab = InfoTab::Map,
+            key if key == self.keys.scroll_up => {
+                let page = self.feed_layout.page_height.max(1);
+                self.feed_scroll = (self.feed_scroll + page).min(self.feed_layout.max_scroll);
+            }
+            key if key == self.keys.scroll_down => {
+                let page = self.feed_layout.page_height.max(1);
+                self.feed_scroll = self.feed_scroll.saturating_sub(page);
+            }
+            Key::Up if on_map => {
+                self.selected_map_line = self.selected_map_line.saturating_sub(1);
+            }
+            Key::Down if on_map => {
+                let line_count = game_info.map.tree(game_info.current_room.number).len();
+                if self.selected_map_line + 1 < line_count {
+                    self.selected_map_line += 1;
+                }
+            }
+            Key::Up if self.focus == Focus::Rooms => {
+                self.selected_room = self.selected_room.min(room_count).saturating_sub(1);
+            }
+            Key::Down if self.focus == Focus::Rooms && self.selected_room + 1 < room_count => {
+                self.selected_room += 1;
+            }
+            Key::Up if self.focus == Focus::Entities => {
+                self.selected_entity = self.selected_entity.min(entity_count).saturating_sub(1);
+            }
+            Key::Down
+                if self.focus == Focus::Entities && self.selected_entity + 1 < entity_count =>
+            {
+                self.selected_entity += 1;
+            }
+            Key::Char(c) => self.input.insert(c),
+            Key::Ctrl('u') => self.input.clear(),
+            Key::Ctrl('a') | Key::Home => self.input.home(),
+            Key::Ctrl('e') | Key::End => self.input.end(),
+            Key::Backspace => self.input.backspace(),
+            Key::Delete => self.input.delete(),
+            Key::Left => self.input.left(),
+            Key::Right => self.input.right(),
+            _ => {}
+        }
+        None
+    }
+}
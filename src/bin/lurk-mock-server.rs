@@ -0,0 +1,88 @@
+extern crate chrono;
+extern crate liblurk;
+extern crate serde;
+#[macro_use]
+extern crate serde_derive;
+extern crate toml;
+
+// The server shares the client's packet handling, which needs these too.
+#[allow(dead_code)]
+#[path = "../combat.rs"]
+mod combat;
+#[allow(dead_code)]
+#[path = "../error.rs"]
+mod error;
+#[allow(dead_code)]
+#[path = "../game.rs"]
+mod game;
+#[allow(dead_code)]
+#[path = "../map.rs"]
+mod map;
+#[allow(dead_code)]
+#[path = "../notification.rs"]
+mod notification;
+#[allow(dead_code)]
+#[path = "../protocol.rs"]
+mod protocol;
+#[path = "../server.rs"]
+mod server;
+
+use std::env;
+use std::process;
+
+use server::{Server, World};
+
+const USAGE: &str = "Usage: lurk-mock-server [--port <port>] [--world <file>]";
+
+const DEFAULT_PORT: u16 = 5050;
+
+fn options() -> Result<(u16, Option<String>), String> {
+    let mut port = DEFAULT_PORT;
+    let mut world = None;
+
+    let mut args = env::args().skip(1);
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--port" => {
+                let value = args.next()
+                    .ok_or_else(|| String::from("--port requires a value."))?;
+                port = value
+                    .parse::<u16>()
+                    .map_err(|_| format!("Invalid port '{}'.", value))?;
+            }
+            "--world" => {
+                world = Some(args.next()
+                    .ok_or_else(|| String::from("--world requires a value."))?);
+            }
+            other => return Err(format!("Unknown argument '{}'.", other)),
+        }
+    }
+
+    Ok((port, world))
+}
+
+fn main() {
+    let (port, world_path) = options().unwrap_or_else(|e| {
+        eprintln!("{}\n{}", e, USAGE);
+        process::exit(1);
+    });
+
+    let world = match world_path {
+        Some(path) => World::load(&path),
+        None => Ok(World::example()),
+    };
+    let world = world.unwrap_or_else(|e| {
+        eprintln!("{}", e);
+        process::exit(1);
+    });
+
+    let server = Server::bind(("127.0.0.1", port), world).unwrap_or_else(|e| {
+        eprintln!("{}", e);
+        process::exit(1);
+    });
+    match server.local_addr() {
+        Ok(addr) => println!("Serving on {}", addr),
+        Err(e) => eprintln!("{}", e),
+    }
+    server.run();
+}
@@ -0,0 +1,205 @@
+use std::time::Instant;
+
+use termion::event::Key;
+
+use game::{Entity, Game};
+use input::InputLine;
+
+// LURK character names are sent in a fixed 32 byte field.
+const MAX_NAME_LENGTH: usize = 32;
+
+#[derive(Clone, Copy, PartialEq)]
+pub enum Field {
+    Name,
+    Description,
+    Attack,
+    Defense,
+    Regen,
+    JoinBattle,
+    Submit,
+}
+
+const FIELDS: [Field; 7] = [
+    Field::Name,
+    Field::Description,
+    Field::Attack,
+    Field::Defense,
+    Field::Regen,
+    Field::JoinBattle,
+    Field::Submit,
+];
+
+pub enum FormStatus {
+    Editing,
+    /// The character was sent at the given time and we're waiting for the
+    /// server's verdict on it.
+    Waiting(Instant),
+    Rejected(String),
+}
+
+/// Checks a character against the limits the server advertised in GAME.
+pub fn validate(entity: &Entity, game: &Game) -> Result<(), String> {
+    if entity.name.is_empty() {
+        return Err(String::from("Your character needs a name."));
+    }
+    if entity.name.len() > MAX_NAME_LENGTH {
+        return Err(format!(
+            "Names can be at most {} bytes long.",
+            MAX_NAME_LENGTH
+        ));
+    }
+    let points_spent =
+        u32::from(entity.attack) + u32::from(entity.defense) + u32::from(entity.regen);
+    if points_spent > u32::from(game.init_points) {
+        return Err(format!(
+            "Only {} points are available, {} are spent.",
+            game.init_points, points_spent
+        ));
+    }
+    if points_spent > u32::from(game.stat_limit) {
+        return Err(format!(
+            "Stats may not total more than {}.",
+            game.stat_limit
+        ));
+    }
+    Ok(())
+}
+
+/// State of the character creation screen shown after connecting.
+pub struct CharacterForm {
+    pub name: InputLine,
+    pub description: InputLine,
+    pub attack: u16,
+    pub defense: u16,
+    pub regen: u16,
+    pub join_battle: bool,
+    pub focus: Field,
+    pub status: FormStatus,
+}
+
+impl Default for CharacterForm {
+    fn default() -> CharacterForm {
+        CharacterForm::new()
+    }
+}
+
+impl CharacterForm {
+    pub fn new() -> CharacterForm {
+        CharacterForm {
+            name: InputLine::default(),
+            description: InputLine::default(),
+            attack: 0,
+            defense: 0,
+            regen: 0,
+            join_battle: true,
+            focus: Field::Name,
+            status: FormStatus::Editing,
+        }
+    }
+
+    pub fn points_spent(&self) -> u32 {
+        u32::from(self.attack) + u32::from(self.defense) + u32::from(self.regen)
+    }
+
+    /// Checks the form against the limits the server advertised in GAME.
+    pub fn validate(&self, game: &Game) -> Result<(), String> {
+        validate(&self.to_entity(), game)
+    }
+
+    pub fn to_entity(&self) -> Entity {
+        Entity {
+            name: self.name.text().trim().to_string(),
+            join_battle: self.join_battle,
+            attack: self.attack,
+            defense: self.defense,
+            regen: self.regen,
+            description: self.description.text().to_string(),
+            ..Entity::default()
+        }
+    }
+
+    fn stat_mut(&mut self) -> Option<&mut u16> {
+        match self.focus {
+            Field::Attack => Some(&mut self.attack),
+            Field::Defense => Some(&mut self.defense),
+            Field::Regen => Some(&mut self.regen),
+            _ => None,
+        }
+    }
+
+    fn move_focus(&mut self, forward: bool) {
+        let index = FIELDS.iter().position(|f| *f == self.focus).unwrap_or(0);
+        let next = if forward {
+            (index + 1) % FIELDS.len()
+        } else {
+            (index + FIELDS.len() - 1) % FIELDS.len()
+        };
+        self.focus = FIELDS[next];
+    }
+
+    /// Edits the form. Returns the character to send once the user submits a
+    /// valid one.
+    pub fn handle_key(&mut self, key: Key, game: &Game) -> Option<Entity> {
+        if let FormStatus::Waiting(_) = self.status {
+            return None;
+        }
+
+        match key {
+            Key::Char('\t') | Key::Down => self.move_focus(true),
+            Key::BackTab | Key::Up => self.move_focus(false),
+            Key::Char('\n') => {
+                if self.focus != Field::Submit {
+                    self.move_focus(true);
+                    return None;
+                }
+                match self.validate(game) {
+                    Ok(()) => {
+                        self.status = FormStatus::Waiting(Instant::now());
+                        return Some(self.to_entity());
+                    }
+                    Err(e) => self.status = FormStatus::Rejected(e),
+                }
+            }
+            _ => match self.focus {
+                Field::Name | Field::Description => {
+                    let line = if self.focus == Field::Name {
+                        &mut self.name
+                    } else {
+                        &mut self.description
+                    };
+                    match key {
+                        Key::Char(c) => line.insert(c),
+                        Key::Backspace => line.backspace(),
+                        Key::Delete => line.delete(),
+                        Key::Left => line.left(),
+                        Key::Right => line.right(),
+                        Key::Home => line.home(),
+                        Key::End => line.end(),
+                        _ => {}
+                    }
+                }
+                Field::JoinBattle => match key {
+                    Key::Char(' ') | Key::Left | Key::Right => {
+                        self.join_battle = !self.join_battle
+                    }
+                    _ => {}
+                },
+                _ => {
+                    if let Some(stat) = self.stat_mut() {
+                        match key {
+                            Key::Right | Key::Char('+') => *stat = stat.saturating_add(1),
+                            Key::Left | Key::Char('-') => *stat = stat.saturating_sub(1),
+                            Key::Backspace => *stat /= 10,
+                            Key::Char(c) if c.is_ascii_digit() => {
+                                let digit = c.to_digit(10).unwrap_or(0) as u16;
+                                *stat = stat.saturating_mul(10).saturating_add(digit);
+                            }
+                            _ => {}
+                        }
+                    }
+                }
+            },
+        }
+        None
+    }
+}
@@ -0,0 +1,113 @@
+use std::env;
+
+pub const USAGE: &str = "Usage: liblurk-rs_client_example [--host <host> --port <port>] \
+                         [--feed-capacity <messages>] [--config <file>] [--record <file>] [--plain | --json]\n       \
+                         liblurk-rs_client_example --replay <file> [--speed <factor>] [--plain | --json]";
+
+pub struct Options {
+    pub server: Option<(String, u16)>,
+    pub feed_capacity: Option<usize>,
+    /// Read instead of the config file in the user's config directory.
+    pub config: Option<String>,
+    /// Where to write every packet sent and received.
+    pub record: Option<String>,
+    /// A recording to play back instead of connecting.
+    pub replay: Option<String>,
+    /// How many times faster than real time to replay.
+    pub speed: f64,
+    /// Read commands from stdin and print events as lines, no screen.
+    pub plain: bool,
+    /// Like `plain`, but one JSON object per line both ways.
+    pub json: bool,
+}
+
+impl Options {
+    pub fn from_args() -> Result<Options, String> {
+        let mut host = None;
+        let mut port = None;
+        let mut feed_capacity = None;
+        let mut config = None;
+        let mut record = None;
+        let mut replay = None;
+        let mut speed = None;
+        let mut plain = false;
+        let mut json = false;
+
+        let mut args = env::args().skip(1);
+        while let Some(arg) = args.next() {
+            match arg.as_str() {
+                "--host" => {
+                    host = Some(args.next()
+                        .ok_or_else(|| String::from("--host requires a value."))?);
+                }
+                "--port" => {
+                    let value = args.next()
+                        .ok_or_else(|| String::from("--port requires a value."))?;
+                    port = Some(value
+                        .parse::<u16>()
+                        .map_err(|_| format!("Invalid port '{}'.", value))?);
+                }
+                "--feed-capacity" => {
+                    let value = args.next()
+                        .ok_or_else(|| String::from("--feed-capacity requires a value."))?;
+                    feed_capacity = Some(value
+                        .parse::<usize>()
+                        .ok()
+                        .filter(|capacity| *capacity > 0)
+                        .ok_or_else(|| format!("Invalid feed capacity '{}'.", value))?);
+                }
+                "--config" => {
+                    config = Some(args.next()
+                        .ok_or_else(|| String::from("--config requires a value."))?);
+                }
+                "--record" => {
+                    record = Some(args.next()
+                        .ok_or_else(|| String::from("--record requires a value."))?);
+                }
+                "--replay" => {
+                    replay = Some(args.next()
+                        .ok_or_else(|| String::from("--replay requires a value."))?);
+                }
+                "--speed" => {
+                    let value = args.next()
+                        .ok_or_else(|| String::from("--speed requires a value."))?;
+                    speed = Some(value
+                        .parse::<f64>()
+                        .ok()
+                        .filter(|speed| speed.is_finite() && *speed > 0.0)
+                        .ok_or_else(|| format!("Invalid speed '{}'.", value))?);
+                }
+                "--plain" => plain = true,
+                "--json" => json = true,
+                other => return Err(format!("Unknown argument '{}'.", other)),
+            }
+        }
+
+        let server = match (host, port) {
+            (Some(host), Some(port)) => Some((host, port)),
+            (None, None) => None,
+            _ => return Err(String::from("--host and --port must be given together.")),
+        };
+
+        if replay.is_some() && (server.is_some() || record.is_some()) {
+            return Err(String::from("--replay can't be combined with --host, --port or --record."));
+        }
+        if plain && json {
+            return Err(String::from("--plain and --json can't be combined."));
+        }
+        if replay.is_none() && speed.is_some() {
+            return Err(String::from("--speed only applies to --replay."));
+        }
+
+        Ok(Options {
+            server,
+            feed_capacity,
+            config,
+            record,
+            replay,
+            speed: speed.unwrap_or(1.0),
+            plain,
+            json,
+        })
+    }
+}
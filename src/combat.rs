@@ -0,0 +1,195 @@
+use std::collections::VecDeque;
+use std::time::{Duration, Instant};
+
+use chrono::prelude::*;
+
+use game::Entity;
+
+const MAX_ENTRIES: usize = 200;
+
+// LURK has no notion of a fight ending, so one is considered over once the
+// server has gone quiet about it for this long.
+const FIGHT_QUIET_SECS: u64 = 5;
+
+#[derive(Clone)]
+pub enum CombatEvent {
+    Damage { name: String, amount: i32 },
+    Regen { name: String, amount: i32 },
+    Death { name: String },
+    Revived { name: String },
+    Gold { name: String, amount: i32 },
+}
+
+#[derive(Clone)]
+pub struct CombatEntry {
+    pub time: DateTime<Local>,
+    pub event: CombatEvent,
+}
+
+/// Running totals for the session, from the player's point of view.
+#[derive(Clone, Default)]
+pub struct CombatTotals {
+    pub fights: u32,
+    pub damage_dealt: u32,
+    pub damage_taken: u32,
+    pub regenerated: u32,
+    pub kills: u32,
+    pub deaths: u32,
+    pub gold_gained: u32,
+    pub gold_lost: u32,
+}
+
+/// Builds a combat log by comparing each CHARACTER update with the last one
+/// seen for the same entity.
+#[derive(Clone, Default)]
+pub struct CombatLog {
+    pub entries: VecDeque<CombatEntry>,
+    pub totals: CombatTotals,
+    fight_activity: Option<Instant>,
+}
+
+impl CombatLog {
+    /// Called when the player starts a fight, so the damage that follows is
+    /// credited to them.
+    pub fn begin_fight(&mut self) {
+        self.totals.fights += 1;
+        self.fight_activity = Some(Instant::now());
+    }
+
+    pub fn in_fight(&self) -> bool {
+        match self.fight_activity {
+            Some(at) => at.elapsed() < Duration::from_secs(FIGHT_QUIET_SECS),
+            None => false,
+        }
+    }
+
+    /// Whether anyone has been hurt or killed since `time`.
+    pub fn blood_since(&self, time: DateTime<Local>) -> bool {
+        self.entries
+            .iter()
+            .rev()
+            .take_while(|entry| entry.time >= time)
+            .any(|entry| {
+                matches!(entry.event, CombatEvent::Damage { .. } | CombatEvent::Death { .. })
+            })
+    }
+
+    fn push(&mut self, event: CombatEvent) {
+        self.entries.push_back(CombatEntry {
+            time: Local::now(),
+            event,
+        });
+        while self.entries.len() > MAX_ENTRIES {
+            self.entries.pop_front();
+        }
+    }
+
+    /// Records what changed between two states of the same entity.
+    pub fn record(&mut self, old: &Entity, new: &Entity, is_player: bool) {
+        let health_change = i32::from(new.health) - i32::from(old.health);
+        let gold_change = i32::from(new.gold) - i32::from(old.gold);
+        let in_fight = self.in_fight();
+
+        if health_change < 0 {
+            self.push(CombatEvent::Damage {
+                name: new.name.clone(),
+                amount: -health_change,
+            });
+            if is_player {
+                self.totals.damage_taken += (-health_change) as u32;
+            } else if in_fight {
+                self.totals.damage_dealt += (-health_change) as u32;
+            }
+        } else if health_change > 0 {
+            self.push(CombatEvent::Regen {
+                name: new.name.clone(),
+                amount: health_change,
+            });
+            if is_player {
+                self.totals.regenerated += health_change as u32;
+            }
+        }
+
+        if old.is_alive && !new.is_alive {
+            self.push(CombatEvent::Death {
+                name: new.name.clone(),
+            });
+            if is_player {
+                self.totals.deaths += 1;
+            } else if in_fight {
+                self.totals.kills += 1;
+            }
+        } else if !old.is_alive && new.is_alive {
+            self.push(CombatEvent::Revived {
+                name: new.name.clone(),
+            });
+        }
+
+        if gold_change != 0 {
+            self.push(CombatEvent::Gold {
+                name: new.name.clone(),
+                amount: gold_change,
+            });
+            if is_player && gold_change > 0 {
+                self.totals.gold_gained += gold_change as u32;
+            } else if is_player {
+                self.totals.gold_lost += (-gold_change) as u32;
+            }
+        }
+
+        // Keep the fight going for as long as health is still changing.
+        if in_fight && (health_change != 0 || old.is_alive != new.is_alive) {
+            self.fight_activity = Some(Instant::now());
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::prelude::*;
+    use chrono::Duration;
+
+    use game::Entity;
+    use super::CombatLog;
+
+    fn entity(name: &str, health: i16, gold: u16) -> Entity {
+        Entity {
+            name: String::from(name),
+            is_alive: health > 0,
+            health,
+            gold,
+            ..Entity::default()
+        }
+    }
+
+    #[test]
+    fn damage_is_credited_to_the_player_only_in_a_fight() {
+        let mut log = CombatLog::default();
+        log.record(&entity("Goblin", 50, 0), &entity("Goblin", 40, 0), false);
+        assert_eq!(log.totals.damage_dealt, 0);
+
+        log.begin_fight();
+        log.record(&entity("Goblin", 40, 5), &entity("Goblin", 0, 5), false);
+        log.record(&entity("Alice", 100, 0), &entity("Alice", 85, 5), true);
+
+        assert_eq!(log.totals.fights, 1);
+        assert_eq!(log.totals.damage_dealt, 40);
+        assert_eq!(log.totals.kills, 1);
+        assert_eq!(log.totals.damage_taken, 15);
+        assert_eq!(log.totals.gold_gained, 5);
+        assert_eq!(log.totals.deaths, 0);
+    }
+
+    #[test]
+    fn only_damage_and_deaths_are_blood() {
+        let mut log = CombatLog::default();
+        let start = Local::now() - Duration::seconds(1);
+        log.record(&entity("Alice", 80, 0), &entity("Alice", 90, 3), true);
+        assert!(!log.blood_since(start));
+        assert_eq!(log.totals.regenerated, 10);
+
+        log.record(&entity("Alice", 90, 3), &entity("Alice", 70, 3), true);
+        assert!(log.blood_since(start));
+        assert!(!log.blood_since(Local::now() + Duration::seconds(1)));
+    }
+}
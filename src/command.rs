@@ -0,0 +1,370 @@
+use std::fmt;
+
+use chrono::prelude::*;
+
+use game::{Entity, GameInformation, Message};
+use protocol::ClientMessage;
+
+pub const HELP: &str = "Commands: /character <name> <attack> <defense> <regen> [description], \
+                        /go <room#|name>, /travel <room#|name>, /fight, /pvp <name>, \
+                        /loot <name>, /start, /say <text>, /tell <name> <text>, /reply <text>, \
+                        /dm [name], /theme [name], /errors, /reload, /leave, /quit.";
+
+pub enum RoomTarget {
+    Number(u16),
+    Name(String),
+}
+
+pub enum Command {
+    /// Makes a character without the creation screen, for line mode.
+    Character(Entity),
+    Go(RoomTarget),
+    /// Walks to any discovered room, one exit at a time.
+    Travel(RoomTarget),
+    Fight,
+    Pvp(String),
+    Loot(String),
+    Start,
+    Say(String),
+    Tell(String, String),
+    Reply(String),
+    /// Shows the conversation with one player in the feed, or everything
+    /// again when no name is given.
+    Conversation(Option<String>),
+    /// Switches to a preset theme, or lists them when no name is given.
+    Theme(Option<String>),
+    /// Lists the errors the server has sent.
+    Errors,
+    /// Loads the scripts in the config directory again.
+    Reload,
+    Leave,
+    Quit,
+    Help,
+}
+
+fn room_target(args: &str, usage: &str) -> Result<RoomTarget, String> {
+    let target = required(args, usage)?;
+    Ok(match target.parse::<u16>() {
+        Ok(number) => RoomTarget::Number(number),
+        Err(_) => RoomTarget::Name(target),
+    })
+}
+
+/// `args` trimmed, or how the command should have been used if that leaves
+/// nothing.
+pub fn required(args: &str, usage: &str) -> Result<String, String> {
+    let args = args.trim();
+    if args.is_empty() {
+        Err(format!("Usage: {}", usage))
+    } else {
+        Ok(args.to_string())
+    }
+}
+
+// Names may contain spaces, in which case they can be quoted.
+fn split_name(args: &str) -> Option<(String, String)> {
+    let args = args.trim_start();
+    if let Some(quoted) = args.strip_prefix('"') {
+        let end = quoted.find('"')?;
+        Some((quoted[..end].to_string(), quoted[end + 1..].trim().to_string()))
+    } else {
+        let end = args.find(char::is_whitespace)?;
+        Some((args[..end].to_string(), args[end..].trim().to_string()))
+    }
+}
+
+fn character(args: &str) -> Result<Command, String> {
+    let usage = "/character <name> <attack> <defense> <regen> [description]";
+    let (name, mut rest) = split_name(args).ok_or_else(|| format!("Usage: {}", usage))?;
+
+    let mut stats = [0u16; 3];
+    for stat in &mut stats {
+        let word = rest.split_whitespace().next().unwrap_or("").to_string();
+        *stat = word.parse().map_err(|_| format!("Usage: {}", usage))?;
+        rest = rest.trim_start()[word.len()..].to_string();
+    }
+
+    Ok(Command::Character(Entity {
+        name,
+        join_battle: true,
+        attack: stats[0],
+        defense: stats[1],
+        regen: stats[2],
+        description: rest.trim().to_string(),
+        ..Entity::default()
+    }))
+}
+
+/// Parses a line from the input section. Text without a leading slash is
+/// said to the room.
+pub fn parse(line: &str) -> Result<Command, String> {
+    let line = line.trim();
+    if !line.starts_with('/') {
+        return required(line, "/say <text>").map(Command::Say);
+    }
+
+    let (name, args) = match line.find(char::is_whitespace) {
+        Some(index) => (&line[1..index], &line[index..]),
+        None => (&line[1..], ""),
+    };
+
+    match name.to_lowercase().as_str() {
+        "character" => character(args),
+        "go" => Ok(Command::Go(room_target(args, "/go <room#|name>")?)),
+        "travel" => Ok(Command::Travel(room_target(args, "/travel <room#|name>")?)),
+        "fight" => Ok(Command::Fight),
+        "pvp" => Ok(Command::Pvp(required(args, "/pvp <name>")?)),
+        "loot" => Ok(Command::Loot(required(args, "/loot <name>")?)),
+        "start" => Ok(Command::Start),
+        "say" => Ok(Command::Say(required(args, "/say <text>")?)),
+        "tell" => match split_name(args) {
+            Some((ref target, ref text)) if !target.is_empty() && !text.is_empty() => {
+                Ok(Command::Tell(target.clone(), text.clone()))
+            }
+            _ => Err(String::from("Usage: /tell <name> <text>")),
+        },
+        "reply" | "r" => Ok(Command::Reply(required(args, "/reply <text>")?)),
+        "dm" => {
+            let name = args.trim();
+            Ok(Command::Conversation(if name.is_empty() {
+                None
+            } else {
+                Some(name.to_string())
+            }))
+        }
+        "theme" => {
+            let name = args.trim();
+            Ok(Command::Theme(if name.is_empty() {
+                None
+            } else {
+                Some(name.to_string())
+            }))
+        }
+        "errors" => Ok(Command::Errors),
+        "reload" => Ok(Command::Reload),
+        "leave" => Ok(Command::Leave),
+        "quit" => Ok(Command::Quit),
+        "help" => Ok(Command::Help),
+        _ => Err(format!("Unknown command '/{}'. Try /help.", name)),
+    }
+}
+
+fn find_room(game_info: &GameInformation, target: &RoomTarget) -> Result<u16, String> {
+    match *target {
+        RoomTarget::Number(number) => Ok(number),
+        RoomTarget::Name(ref name) => game_info
+            .adjacent_rooms
+            .iter()
+            .find(|room| room.name.eq_ignore_ascii_case(name))
+            .map(|room| room.number)
+            .ok_or_else(|| format!("No connected room is named '{}'.", name)),
+    }
+}
+
+fn reply_target(game_info: &GameInformation) -> Result<String, String> {
+    game_info
+        .last_whisper_from
+        .clone()
+        .ok_or_else(|| String::from("Nobody has whispered to you yet."))
+}
+
+impl Command {
+    /// The packets that carry out this command, if any.
+    pub fn to_messages(&self, game_info: &GameInformation) -> Result<Vec<ClientMessage>, String> {
+        let sender = game_info.player.name.clone();
+
+        match *self {
+            Command::Character(ref entity) => Ok(vec![ClientMessage::Character(entity.clone())]),
+            Command::Go(ref target) => Ok(vec![
+                ClientMessage::ChangeRoom(find_room(game_info, target)?),
+            ]),
+            Command::Fight => Ok(vec![ClientMessage::Fight]),
+            Command::Pvp(ref target) => Ok(vec![ClientMessage::PvpFight(target.clone())]),
+            Command::Loot(ref target) => Ok(vec![ClientMessage::Loot(target.clone())]),
+            Command::Start => Ok(vec![ClientMessage::Start]),
+            // LURK messages have a single receiver, so saying something to
+            // the room means telling every player in it.
+            Command::Say(ref content) => {
+                let messages: Vec<ClientMessage> = game_info
+                    .current_enemies
+                    .iter()
+                    .filter(|e| !e.is_monster)
+                    .map(|e| ClientMessage::Message {
+                        receiver: e.name.clone(),
+                        sender: sender.clone(),
+                        content: content.clone(),
+                    })
+                    .collect();
+                if messages.is_empty() {
+                    Err(String::from("There is nobody here to hear you."))
+                } else {
+                    Ok(messages)
+                }
+            }
+            Command::Tell(ref receiver, ref content) => Ok(vec![ClientMessage::Message {
+                receiver: receiver.clone(),
+                sender,
+                content: content.clone(),
+            }]),
+            Command::Reply(ref content) => Ok(vec![ClientMessage::Message {
+                receiver: reply_target(game_info)?,
+                sender,
+                content: content.clone(),
+            }]),
+            Command::Leave => Ok(vec![ClientMessage::Leave]),
+            // Only the first step is known up front, see `travel::Journey`.
+            Command::Travel(_)
+            | Command::Quit
+            | Command::Help
+            | Command::Conversation(_)
+            | Command::Theme(_)
+            | Command::Errors
+            | Command::Reload => Ok(Vec::new()),
+        }
+    }
+
+    /// Our own copy of a chat message once it's been sent, since the server
+    /// doesn't send it back. Saying something to the room has no receiver.
+    pub fn echo(&self, game_info: &GameInformation) -> Option<Message> {
+        let (receiver, content) = match *self {
+            Command::Say(ref content) => (String::new(), content.clone()),
+            Command::Tell(ref receiver, ref content) => (receiver.clone(), content.clone()),
+            Command::Reply(ref content) => (reply_target(game_info).ok()?, content.clone()),
+            _ => return None,
+        };
+
+        Some(Message {
+            sender: game_info.player.name.clone(),
+            receiver,
+            content,
+            received: Local::now(),
+            notice: false,
+        })
+    }
+}
+
+impl fmt::Display for RoomTarget {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            RoomTarget::Number(number) => write!(f, "{}", number),
+            RoomTarget::Name(ref name) => write!(f, "{}", name),
+        }
+    }
+}
+
+// The reverse of `split_name`.
+fn quoted(name: &str) -> String {
+    if name.contains(char::is_whitespace) {
+        format!("\"{}\"", name)
+    } else {
+        name.to_string()
+    }
+}
+
+/// The command as it would be typed.
+impl fmt::Display for Command {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            Command::Character(ref entity) => {
+                write!(
+                    f,
+                    "/character {} {} {} {}",
+                    quoted(&entity.name),
+                    entity.attack,
+                    entity.defense,
+                    entity.regen
+                )?;
+                if !entity.description.is_empty() {
+                    write!(f, " {}", entity.description)?;
+                }
+                Ok(())
+            }
+            Command::Go(ref target) => write!(f, "/go {}", target),
+            Command::Travel(ref target) => write!(f, "/travel {}", target),
+            Command::Fight => write!(f, "/fight"),
+            Command::Pvp(ref target) => write!(f, "/pvp {}", target),
+            Command::Loot(ref target) => write!(f, "/loot {}", target),
+            Command::Start => write!(f, "/start"),
+            Command::Say(ref content) => write!(f, "/say {}", content),
+            Command::Tell(ref receiver, ref content) => {
+                write!(f, "/tell {} {}", quoted(receiver), content)
+            }
+            Command::Reply(ref content) => write!(f, "/reply {}", content),
+            Command::Conversation(Some(ref name)) => write!(f, "/dm {}", name),
+            Command::Conversation(None) => write!(f, "/dm"),
+            Command::Theme(Some(ref name)) => write!(f, "/theme {}", name),
+            Command::Theme(None) => write!(f, "/theme"),
+            Command::Errors => write!(f, "/errors"),
+            Command::Reload => write!(f, "/reload"),
+            Command::Leave => write!(f, "/leave"),
+            Command::Quit => write!(f, "/quit"),
+            Command::Help => write!(f, "/help"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{parse, Command, RoomTarget};
+
+    #[test]
+    fn text_is_said_to_the_room() {
+        match parse("  hello there ") {
+            Ok(Command::Say(ref text)) if text == "hello there" => {}
+            _ => panic!("Expected /say hello there."),
+        }
+        assert!(parse("   ").is_err());
+        assert!(parse("/say   ").is_err());
+    }
+
+    #[test]
+    fn rooms_are_numbers_or_names() {
+        match parse("/GO 3") {
+            Ok(Command::Go(RoomTarget::Number(3))) => {}
+            _ => panic!("Expected /go 3."),
+        }
+        match parse("/travel Great Hall") {
+            Ok(Command::Travel(RoomTarget::Name(ref name))) if name == "Great Hall" => {}
+            _ => panic!("Expected /travel Great Hall."),
+        }
+        assert!(parse("/go").is_err());
+        assert!(parse("/dance").is_err());
+    }
+
+    #[test]
+    fn names_can_be_quoted() {
+        match parse("/tell \"Bob the Bold\" meet me in the hall") {
+            Ok(Command::Tell(ref name, ref text))
+                if name == "Bob the Bold" && text == "meet me in the hall" => {}
+            _ => panic!("Expected a tell to Bob the Bold."),
+        }
+        match parse("/tell Bob hi") {
+            Ok(Command::Tell(ref name, ref text)) if name == "Bob" && text == "hi" => {}
+            _ => panic!("Expected a tell to Bob."),
+        }
+        assert!(parse("/tell Bob").is_err());
+        assert!(parse("/tell \"Bob hi").is_err());
+        assert!(parse("/tell \"Bob\"").is_err());
+    }
+
+    #[test]
+    fn characters_need_every_stat() {
+        match parse("/character \"Sir Bob\" 10 20 30 A knight of sorts.") {
+            Ok(Command::Character(ref entity)) => {
+                assert_eq!(entity.name, "Sir Bob");
+                assert_eq!((entity.attack, entity.defense, entity.regen), (10, 20, 30));
+                assert_eq!(entity.description, "A knight of sorts.");
+                assert!(entity.join_battle);
+            }
+            _ => panic!("Expected a character."),
+        }
+        match parse("/character Bob 10 20 30") {
+            Ok(Command::Character(ref entity)) => assert!(entity.description.is_empty()),
+            _ => panic!("Expected a character."),
+        }
+        assert!(parse("/character Bob 10 20").is_err());
+        assert!(parse("/character Bob 10 twenty 30").is_err());
+        assert!(parse("/character Bob 10 20 -30").is_err());
+        assert!(parse("/character").is_err());
+    }
+}
@@ -0,0 +1,297 @@
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::result;
+
+use dirs;
+use toml;
+
+use error::{Error, Result};
+use keys::{parse_key, KeyBindings};
+use theme::{self, parse_color, Theme};
+
+// Under the user's config directory, `~/.config` unless XDG_CONFIG_HOME says
+// otherwise.
+const APP_DIR: &str = "lurk-client";
+const CONFIG_FILE: &str = "config.toml";
+
+/// Percentages of the screen given to each panel of the main screen.
+#[derive(Clone, Copy, PartialEq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct PanelSizes {
+    pub top_bar: u16,
+    pub info: u16,
+    pub bottom: u16,
+    pub player: u16,
+    pub feed: u16,
+    pub entities: u16,
+}
+
+impl Default for PanelSizes {
+    fn default() -> PanelSizes {
+        PanelSizes {
+            top_bar: 10,
+            info: 55,
+            bottom: 30,
+            player: 30,
+            feed: 40,
+            entities: 30,
+        }
+    }
+}
+
+impl PanelSizes {
+    fn validate(&self) -> result::Result<(), String> {
+        let total = |sizes: &[u16]| -> u32 { sizes.iter().map(|size| u32::from(*size)).sum() };
+        if total(&[self.top_bar, self.info, self.bottom]) > 100 {
+            return Err(String::from("layout: top_bar, info and bottom add up to over 100."));
+        }
+        if total(&[self.player, self.feed, self.entities]) > 100 {
+            return Err(String::from("layout: player, feed and entities add up to over 100."));
+        }
+        Ok(())
+    }
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct ServerEntry {
+    host: String,
+    port: u16,
+}
+
+#[derive(Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+struct ColorNames {
+    border: Option<String>,
+    background: Option<String>,
+    text: Option<String>,
+    whisper: Option<String>,
+    danger: Option<String>,
+    info: Option<String>,
+    good: Option<String>,
+    gold: Option<String>,
+    muted: Option<String>,
+}
+
+#[derive(Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+struct KeyNames {
+    quit: Option<String>,
+    cycle_focus: Option<String>,
+    room_tab: Option<String>,
+    combat_tab: Option<String>,
+    map_tab: Option<String>,
+    scroll_up: Option<String>,
+    scroll_down: Option<String>,
+    stop_travel: Option<String>,
+    next_theme: Option<String>,
+}
+
+/// The config file as written, everything in it is optional.
+#[derive(Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+struct ConfigFile {
+    feed_capacity: Option<usize>,
+    server: Option<ServerEntry>,
+    /// The preset `colors` are applied on top of.
+    theme: Option<String>,
+    colors: ColorNames,
+    layout: PanelSizes,
+    keys: KeyNames,
+}
+
+fn set<T, F>(target: &mut T, value: &Option<String>, parse: F) -> result::Result<(), String>
+where
+    F: Fn(&str) -> result::Result<T, String>,
+{
+    if let Some(ref value) = *value {
+        *target = parse(value)?;
+    }
+    Ok(())
+}
+
+/// Settings read from the config file, with defaults for anything it
+/// doesn't mention.
+#[derive(Clone)]
+pub struct Config {
+    pub theme_name: String,
+    pub theme: Theme,
+    pub panels: PanelSizes,
+    pub keys: KeyBindings,
+    pub server: Option<(String, u16)>,
+    pub feed_capacity: Option<usize>,
+    /// The directory the config file was read from, or would have been.
+    /// Scripts are kept there too.
+    pub dir: Option<PathBuf>,
+}
+
+impl Default for Config {
+    fn default() -> Config {
+        Config {
+            theme_name: String::from(theme::PRESETS[0]),
+            theme: Theme::default(),
+            panels: PanelSizes::default(),
+            keys: KeyBindings::default(),
+            server: None,
+            feed_capacity: None,
+            dir: None,
+        }
+    }
+}
+
+impl Config {
+    pub fn parse(text: &str) -> Result<Config> {
+        let file: ConfigFile = toml::from_str(text).map_err(|e| Error::Config {
+            message: String::from("Failed to read settings"),
+            source: Some(Box::new(e)),
+        })?;
+        Config::from_file(file).map_err(Error::config)
+    }
+
+    fn from_file(file: ConfigFile) -> result::Result<Config, String> {
+        let mut config = Config::default();
+
+        if let Some(ref name) = file.theme {
+            config.theme = Theme::preset(name).ok_or_else(|| theme::unknown_preset(name))?;
+            config.theme_name = name.to_lowercase();
+        }
+
+        let colors = &file.colors;
+        let theme = &mut config.theme;
+        set(&mut theme.border, &colors.border, parse_color)?;
+        set(&mut theme.background, &colors.background, parse_color)?;
+        set(&mut theme.text, &colors.text, parse_color)?;
+        set(&mut theme.whisper, &colors.whisper, parse_color)?;
+        set(&mut theme.danger, &colors.danger, parse_color)?;
+        set(&mut theme.info, &colors.info, parse_color)?;
+        set(&mut theme.good, &colors.good, parse_color)?;
+        set(&mut theme.gold, &colors.gold, parse_color)?;
+        set(&mut theme.muted, &colors.muted, parse_color)?;
+
+        let names = &file.keys;
+        let keys = &mut config.keys;
+        set(&mut keys.quit, &names.quit, parse_key)?;
+        set(&mut keys.cycle_focus, &names.cycle_focus, parse_key)?;
+        set(&mut keys.room_tab, &names.room_tab, parse_key)?;
+        set(&mut keys.combat_tab, &names.combat_tab, parse_key)?;
+        set(&mut keys.map_tab, &names.map_tab, parse_key)?;
+        set(&mut keys.scroll_up, &names.scroll_up, parse_key)?;
+        set(&mut keys.scroll_down, &names.scroll_down, parse_key)?;
+        set(&mut keys.stop_travel, &names.stop_travel, parse_key)?;
+        set(&mut keys.next_theme, &names.next_theme, parse_key)?;
+        keys.validate()?;
+
+        file.layout.validate()?;
+        config.panels = file.layout;
+
+        if file.feed_capacity == Some(0) {
+            return Err(String::from("feed_capacity must be at least 1."));
+        }
+        config.feed_capacity = file.feed_capacity;
+        config.server = file.server.map(|server| (server.host, server.port));
+
+        Ok(config)
+    }
+
+    /// Reads the config from `path`, or from the default location when no
+    /// path is given. Only a missing default file is allowed.
+    pub fn load(path: Option<&str>) -> Result<Config> {
+        let (path, required) = match path {
+            Some(path) => (PathBuf::from(path), true),
+            None => match config_dir() {
+                Some(dir) => (dir.join(CONFIG_FILE), false),
+                None => return Ok(Config::default()),
+            },
+        };
+
+        let config = match fs::read_to_string(&path) {
+            Ok(text) => Config::parse(&text).map_err(|e| Error::Config {
+                message: format!("Invalid config {}", path.display()),
+                source: Some(Box::new(e)),
+            })?,
+            Err(ref e) if !required && e.kind() == io::ErrorKind::NotFound => Config::default(),
+            Err(e) => {
+                return Err(Error::Config {
+                    message: format!("Failed to read {}", path.display()),
+                    source: Some(Box::new(e)),
+                })
+            }
+        };
+
+        Ok(Config {
+            dir: path.parent().map(Path::to_path_buf),
+            ..config
+        })
+    }
+}
+
+/// Where the config file and anything else the user customises lives.
+fn config_dir() -> Option<PathBuf> {
+    dirs::config_dir().map(|dir| dir.join(APP_DIR))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::PathBuf;
+
+    use super::Config;
+
+    #[test]
+    fn example_config_is_the_defaults() {
+        let path = concat!(env!("CARGO_MANIFEST_DIR"), "/data/config.example.toml");
+        let config = Config::load(Some(path)).unwrap();
+        let defaults = Config::default();
+
+        assert_eq!(config.theme_name, defaults.theme_name);
+        assert!(config.theme == defaults.theme);
+        assert!(config.panels == defaults.panels);
+        assert!(config.keys == defaults.keys);
+        assert_eq!(config.server, None);
+        assert_eq!(config.feed_capacity, Some(500));
+        let data = PathBuf::from(concat!(env!("CARGO_MANIFEST_DIR"), "/data"));
+        assert_eq!(config.dir, Some(data));
+    }
+
+    #[test]
+    fn unknown_settings_are_errors() {
+        assert!(Config::parse("[colors]\nborder = \"plaid\"").is_err());
+        assert!(Config::parse("[keys]\nquit = \"ctrl-\"").is_err());
+        assert!(Config::parse("[layout]\ninfo = 95").is_err());
+        assert!(Config::parse("colour = \"red\"").is_err());
+        assert!(Config::parse("theme = \"neon\"").is_err());
+        assert!(Config::parse("[layout]\ntop_bar = 65535\ninfo = 1").is_err());
+    }
+
+    #[test]
+    fn keys_are_left_for_typing_and_bound_once() {
+        let error = Config::parse("[keys]\nmap_tab = \"m\"").err().unwrap();
+        assert_eq!(
+            error.report(),
+            "keys: map_tab can't be 'm', it's needed for typing. Try ctrl-m or alt-m."
+        );
+        assert!(Config::parse("[keys]\nquit = \"space\"").is_err());
+
+        let error = Config::parse("[keys]\nquit = \"backspace\"").err().unwrap();
+        assert_eq!(
+            error.report(),
+            "keys: quit can't be Backspace, it's needed for editing and selecting."
+        );
+        assert!(Config::parse("[keys]\nscroll_up = \"up\"").is_err());
+        assert!(Config::parse("[keys]\nquit = \"ctrl-u\"").is_err());
+
+        let error = Config::parse("[keys]\nnext_theme = \"f1\"").err().unwrap();
+        assert_eq!(error.report(), "keys: room_tab and next_theme are both F1.");
+
+        assert!(Config::parse("[keys]\nmap_tab = \"ctrl-m\"\nnext_theme = \"f3\"").is_ok());
+    }
+
+    #[test]
+    fn errors_keep_their_cause() {
+        let error = Config::parse("[colors]\nborder = \"plaid\"").err().unwrap();
+        assert_eq!(error.report(), "Unknown color 'plaid'.");
+
+        let error = Config::parse("colour = \"red\"").err().unwrap();
+        assert!(error.report().starts_with("Failed to read settings: unknown field `colour`"));
+    }
+}
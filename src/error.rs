@@ -0,0 +1,115 @@
+use std::error;
+use std::fmt;
+use std::io;
+use std::result;
+
+pub type Result<T> = result::Result<T, Error>;
+
+/// Something that went wrong outside of the game itself. Messages say what
+/// the client was doing, the underlying error is kept as the source.
+#[derive(Debug)]
+pub enum Error {
+    /// Setting up, drawing to or resizing the terminal.
+    Terminal { action: String, source: io::Error },
+    /// Talking to the server over the socket.
+    Network { action: String, source: io::Error },
+    /// The server sent something we couldn't make sense of.
+    Protocol(String),
+    /// The config file couldn't be read or has bad settings in it.
+    Config {
+        message: String,
+        source: Option<Box<dyn error::Error + Send + Sync>>,
+    },
+    /// Writing a session recording or reading one back.
+    Recording {
+        message: String,
+        source: Box<dyn error::Error + Send + Sync>,
+    },
+}
+
+impl Error {
+    /// For `map_err`, wraps an io error from the terminal.
+    pub fn terminal<S: Into<String>>(action: S) -> impl FnOnce(io::Error) -> Error {
+        let action = action.into();
+        move |source| Error::Terminal { action, source }
+    }
+
+    /// For `map_err`, wraps an io error from the connection.
+    pub fn network<S: Into<String>>(action: S) -> impl FnOnce(io::Error) -> Error {
+        let action = action.into();
+        move |source| Error::Network { action, source }
+    }
+
+    /// For `map_err`, wraps whatever went wrong with a recording.
+    pub fn recording<S, E>(message: S) -> impl FnOnce(E) -> Error
+    where
+        S: Into<String>,
+        E: error::Error + Send + Sync + 'static,
+    {
+        let message = message.into();
+        move |source| Error::Recording {
+            message,
+            source: Box::new(source),
+        }
+    }
+
+    pub fn config<S: Into<String>>(message: S) -> Error {
+        Error::Config {
+            message: message.into(),
+            source: None,
+        }
+    }
+
+    /// Whether it's worth carrying on after this. A frame that failed to
+    /// draw will be replaced by the next one unless the terminal has gone
+    /// away, and play goes on without a recording, but the connection and
+    /// config don't fix themselves.
+    pub fn is_recoverable(&self) -> bool {
+        match *self {
+            Error::Terminal { ref source, .. } => !matches!(
+                source.kind(),
+                io::ErrorKind::BrokenPipe | io::ErrorKind::UnexpectedEof
+            ),
+            Error::Recording { .. } => true,
+            Error::Network { .. } | Error::Protocol(_) | Error::Config { .. } => false,
+        }
+    }
+
+    /// The message followed by every cause under it, for logging.
+    pub fn report(&self) -> String {
+        let mut report = self.to_string();
+        let mut source = error::Error::source(self);
+        while let Some(cause) = source {
+            report.push_str(&format!(": {}", cause));
+            source = cause.source();
+        }
+        report
+    }
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            Error::Terminal { ref action, .. } | Error::Network { ref action, .. } => {
+                write!(f, "{}", action)
+            }
+            Error::Protocol(ref message)
+            | Error::Config { ref message, .. }
+            | Error::Recording { ref message, .. } => write!(f, "{}", message),
+        }
+    }
+}
+
+impl error::Error for Error {
+    fn source(&self) -> Option<&(dyn error::Error + 'static)> {
+        match *self {
+            Error::Terminal { ref source, .. } | Error::Network { ref source, .. } => Some(source),
+            Error::Protocol(_) => None,
+            Error::Config { ref source, .. } => match *source {
+                Some(ref source) => Some(&**source),
+                None => None,
+            },
+            Error::Recording { ref source, .. } => Some(&**source),
+        }
+    }
+}
@@ -0,0 +1,124 @@
+use std::io::{self, BufRead};
+use std::sync::mpsc::{self, Receiver, RecvError, Sender};
+use std::thread;
+use std::time::Duration;
+
+use termion;
+use termion::event::Event as InputEvent;
+use termion::input::TermRead;
+
+use error::Error;
+use game::Game;
+use net::{Connection, Status};
+use protocol::ServerMessage;
+
+// How often the terminal size is checked, there's no portable resize
+// notification to wait on.
+const RESIZE_POLL_MS: u64 = 250;
+
+/// Everything the main loop reacts to.
+pub enum Event {
+    Input(InputEvent),
+    /// A line read from stdin in line mode.
+    Line(String),
+    /// Stdin was closed in line mode.
+    EndOfInput,
+    Tick,
+    Server(ServerMessage),
+    Disconnected(Error),
+    /// The connection is being retried.
+    Status(Status),
+    /// A new connection to the same server, handshake done.
+    Reconnected(Connection, Game),
+    Resize,
+}
+
+/// Gathers events from the keyboard, a clock, the terminal size and the
+/// network onto one channel.
+pub struct Events {
+    tx: Sender<Event>,
+    rx: Receiver<Event>,
+}
+
+impl Events {
+    pub fn new(tick_rate: Duration) -> Events {
+        let (tx, rx) = mpsc::channel();
+
+        {
+            let tx = tx.clone();
+            thread::spawn(move || {
+                let stdin = io::stdin();
+                for evt in stdin.events().flatten() {
+                    if tx.send(Event::Input(evt)).is_err() {
+                        return;
+                    }
+                }
+            });
+        }
+
+        spawn_ticks(tx.clone(), tick_rate);
+
+        {
+            let tx = tx.clone();
+            thread::spawn(move || {
+                let mut last_size = termion::terminal_size().ok();
+                loop {
+                    thread::sleep(Duration::from_millis(RESIZE_POLL_MS));
+                    let size = termion::terminal_size().ok();
+                    if size != last_size {
+                        last_size = size;
+                        if tx.send(Event::Resize).is_err() {
+                            return;
+                        }
+                    }
+                }
+            });
+        }
+
+        Events { tx, rx }
+    }
+
+    /// Like `new`, but reads stdin a line at a time instead of key by key,
+    /// and doesn't watch the terminal.
+    pub fn lines(tick_rate: Duration) -> Events {
+        let (tx, rx) = mpsc::channel();
+
+        {
+            let tx = tx.clone();
+            thread::spawn(move || {
+                let stdin = io::stdin();
+                for line in stdin.lock().lines() {
+                    match line {
+                        Ok(line) => if tx.send(Event::Line(line)).is_err() {
+                            return;
+                        },
+                        Err(_) => break,
+                    }
+                }
+                let _ = tx.send(Event::EndOfInput);
+            });
+        }
+
+        spawn_ticks(tx.clone(), tick_rate);
+
+        Events { tx, rx }
+    }
+
+    /// A handle for other producers, like the network reader.
+    pub fn sender(&self) -> Sender<Event> {
+        self.tx.clone()
+    }
+
+    pub fn next(&self) -> Result<Event, RecvError> {
+        self.rx.recv()
+    }
+}
+
+fn spawn_ticks(tx: Sender<Event>, tick_rate: Duration) {
+    thread::spawn(move || loop {
+        thread::sleep(tick_rate);
+        if tx.send(Event::Tick).is_err() {
+            return;
+        }
+    });
+}
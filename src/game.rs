@@ -1,6 +1,13 @@
-use std::collections::VecDeque;
+use std::collections::{HashMap, HashSet, VecDeque};
 
-#[derive(Clone)]
+use chrono::prelude::*;
+
+use combat::CombatLog;
+use map::RoomMap;
+use notification::Notifications;
+use protocol::ServerMessage;
+
+#[derive(Clone, Default, Serialize, Deserialize)]
 pub struct Entity {
     pub name: String,
 
@@ -23,32 +30,250 @@ pub struct Entity {
     pub description: String,
 }
 
-#[derive(Clone)]
+#[derive(Clone, Serialize, Deserialize)]
 pub struct Message {
     pub sender: String,
     pub receiver: String,
     pub content: String,
+    pub received: DateTime<Local>,
+    /// Said by the client itself rather than anyone in the game.
+    #[serde(default)]
+    pub notice: bool,
 }
 
-#[derive(Clone)]
+#[derive(Clone, Default, Serialize, Deserialize)]
 pub struct Room {
     pub name: String,
     pub description: String,
     pub number: u16,
 }
 
-#[derive(Clone)]
+#[derive(Clone, Default, Serialize, Deserialize)]
 pub struct Game {
     pub stat_limit: u16,
     pub init_points: u16,
     pub description: String,
 }
 
+/// How many messages the feed keeps unless told otherwise.
+pub const DEFAULT_MESSAGE_CAPACITY: usize = 500;
+
 pub struct GameInformation {
     pub messages: VecDeque<Message>,
+    /// The oldest messages are dropped once the feed holds this many.
+    pub message_capacity: usize,
+    /// How many messages have ever been added, counting dropped ones.
+    pub message_total: usize,
+    /// Who `/reply` answers.
+    pub last_whisper_from: Option<String>,
+    /// Everyone seen through CHARACTER who isn't a monster. LURK messages
+    /// always name a receiver, so only these can be told apart as whispers.
+    pub players: HashSet<String>,
     pub player: Entity,
     pub current_enemies: Vec<Entity>,
     pub current_room: Room,
     pub adjacent_rooms: Vec<Room>,
     pub game: Game,
+    /// The most health seen on each entity, which stands in for their
+    /// maximum since LURK doesn't send one.
+    pub peak_health: HashMap<String, i16>,
+    pub combat: CombatLog,
+    pub map: RoomMap,
+    /// ERRORs from the server, with the commands that caused them.
+    pub notifications: Notifications,
+    /// Whether `player` has been filled in by the server yet, rather than
+    /// being what we asked for.
+    player_synced: bool,
+}
+
+impl GameInformation {
+    pub fn new(game: Game) -> GameInformation {
+        GameInformation {
+            messages: VecDeque::new(),
+            message_capacity: DEFAULT_MESSAGE_CAPACITY,
+            message_total: 0,
+            last_whisper_from: None,
+            players: HashSet::new(),
+            player: Entity::default(),
+            current_enemies: Vec::new(),
+            current_room: Room::default(),
+            adjacent_rooms: Vec::new(),
+            game,
+            peak_health: HashMap::new(),
+            combat: CombatLog::default(),
+            map: RoomMap::default(),
+            notifications: Notifications::default(),
+            player_synced: false,
+        }
+    }
+
+    /// Whether `message` was sent privately to the player by another
+    /// player. Narration and monsters talking are addressed to the player
+    /// too, but aren't whispers.
+    pub fn is_whisper(&self, message: &Message) -> bool {
+        !self.player.name.is_empty() && message.receiver == self.player.name
+            && message.sender != self.player.name
+            && self.players.contains(&message.sender)
+    }
+
+    pub fn push_message(&mut self, message: Message) {
+        if self.is_whisper(&message) {
+            self.last_whisper_from = Some(message.sender.clone());
+        }
+        self.messages.push_back(message);
+        self.message_total += 1;
+        while self.messages.len() > self.message_capacity {
+            self.messages.pop_front();
+        }
+    }
+
+    /// The messages added since `message_total` was `total`, as many of them
+    /// as the feed still holds.
+    pub fn messages_since<'a>(&'a self, total: usize) -> impl Iterator<Item = &'a Message> + 'a {
+        let unseen = self.message_total.saturating_sub(total);
+        self.messages.iter().skip(self.messages.len().saturating_sub(unseen))
+    }
+
+    /// Adds a message from the client itself to the feed.
+    pub fn notice(&mut self, content: String) {
+        self.push_message(Message {
+            sender: String::from("Client"),
+            receiver: String::new(),
+            content,
+            received: Local::now(),
+            notice: true,
+        });
+    }
+
+    /// Folds a packet from the server into the current state.
+    pub fn apply(&mut self, msg: ServerMessage) {
+        match msg {
+            ServerMessage::Message(message) => self.push_message(message),
+            // Shown as a notification, not in the feed.
+            ServerMessage::Error { code, message } => self.notifications.push(code, message),
+            ServerMessage::Accept(packet_type) => self.notifications.accepted(packet_type),
+            ServerMessage::Version { .. } => {}
+            ServerMessage::Room(room) => {
+                // Connections and occupants belong to the room we just left,
+                // the server follows up with fresh ones for the new room.
+                self.player.location = room.number;
+                self.map.visit(&room);
+                self.current_room = room;
+                self.adjacent_rooms.clear();
+                self.current_enemies.clear();
+            }
+            ServerMessage::Character(entity) => self.update_entity(entity),
+            ServerMessage::Game(game) => self.game = game,
+            ServerMessage::Connection(room) => {
+                self.map.connect(self.current_room.number, &room);
+                match self.adjacent_rooms.iter().position(|r| r.number == room.number) {
+                    Some(index) => self.adjacent_rooms[index] = room,
+                    None => self.adjacent_rooms.push(room),
+                }
+            }
+        }
+    }
+
+    fn update_entity(&mut self, entity: Entity) {
+        let peak = self.peak_health.entry(entity.name.clone()).or_insert(entity.health);
+        *peak = (*peak).max(entity.health);
+
+        if entity.is_monster {
+            self.players.remove(&entity.name);
+        } else if entity.name != self.player.name {
+            self.players.insert(entity.name.clone());
+        }
+
+        if entity.name == self.player.name {
+            if self.player_synced {
+                self.combat.record(&self.player, &entity, true);
+            }
+            self.player = entity;
+            self.player_synced = true;
+            return;
+        }
+
+        let existing = self.current_enemies
+            .iter()
+            .position(|e| e.name == entity.name);
+
+        if entity.location != self.current_room.number {
+            // They've wandered off to another room.
+            if let Some(index) = existing {
+                self.current_enemies.remove(index);
+            }
+            return;
+        }
+
+        match existing {
+            Some(index) => {
+                self.combat.record(&self.current_enemies[index], &entity, false);
+                self.current_enemies[index] = entity;
+            }
+            None => self.current_enemies.push(entity),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::prelude::*;
+
+    use protocol::ServerMessage;
+    use super::{Entity, Game, GameInformation, Message};
+
+    fn said(sender: &str, content: &str) -> Message {
+        Message {
+            sender: String::from(sender),
+            receiver: String::from("Bob"),
+            content: String::from(content),
+            received: Local::now(),
+            notice: false,
+        }
+    }
+
+    fn arrives(game_info: &mut GameInformation, name: &str, is_monster: bool) {
+        game_info.apply(ServerMessage::Character(Entity {
+            name: String::from(name),
+            is_monster,
+            ..Entity::default()
+        }));
+    }
+
+    #[test]
+    fn only_players_whisper() {
+        let mut game_info = GameInformation::new(Game::default());
+        game_info.player.name = String::from("Bob");
+        arrives(&mut game_info, "Bob", false);
+        arrives(&mut game_info, "Goblin", true);
+        arrives(&mut game_info, "Alice", false);
+
+        let narration = said("Narrator", "Welcome to the dungeon.");
+        let growl = said("Goblin", "Grr, who goes there?");
+        assert!(!game_info.is_whisper(&narration));
+        assert!(!game_info.is_whisper(&growl));
+        game_info.apply(ServerMessage::Message(narration));
+        game_info.apply(ServerMessage::Message(growl));
+        assert_eq!(game_info.last_whisper_from, None);
+
+        let whisper = said("Alice", "Psst.");
+        assert!(game_info.is_whisper(&whisper));
+        game_info.apply(ServerMessage::Message(whisper));
+        assert_eq!(game_info.last_whisper_from, Some(String::from("Alice")));
+    }
+
+    #[test]
+    fn only_the_client_gives_notices() {
+        let mut game_info = GameInformation::new(Game::default());
+        game_info.apply(ServerMessage::Message(said("Client", "Not a notice.")));
+        game_info.notice(String::from("A notice."));
+
+        let notices: Vec<&str> = game_info
+            .messages
+            .iter()
+            .filter(|msg| msg.notice)
+            .map(|msg| msg.content.as_str())
+            .collect();
+        assert_eq!(notices, vec!["A notice."]);
+    }
 }
@@ -0,0 +1,72 @@
+use std::io;
+
+use tui::backend::Backend;
+use tui::buffer::Cell;
+use tui::layout::Rect;
+
+/// Draws into memory instead of a TTY, so what the interface renders can be
+/// inspected. Only the symbols are kept, styles are dropped.
+pub struct BufferBackend {
+    width: u16,
+    height: u16,
+    symbols: Vec<String>,
+}
+
+impl BufferBackend {
+    pub fn new(width: u16, height: u16) -> BufferBackend {
+        BufferBackend {
+            width,
+            height,
+            symbols: vec![String::from(" "); width as usize * height as usize],
+        }
+    }
+
+    /// The screen as text, one line per row with trailing blanks trimmed.
+    pub fn contents(&self) -> String {
+        let mut text = String::new();
+        for row in self.symbols.chunks(self.width.max(1) as usize) {
+            let line: String = row.concat();
+            text.push_str(line.trim_end());
+            text.push('\n');
+        }
+        text
+    }
+}
+
+impl Backend for BufferBackend {
+    fn draw<'a, I>(&mut self, content: I) -> Result<(), io::Error>
+    where
+        I: Iterator<Item = (u16, u16, &'a Cell)>,
+    {
+        for (x, y, cell) in content {
+            if x < self.width && y < self.height {
+                let index = y as usize * self.width as usize + x as usize;
+                self.symbols[index] = cell.symbol.clone();
+            }
+        }
+        Ok(())
+    }
+
+    fn hide_cursor(&mut self) -> Result<(), io::Error> {
+        Ok(())
+    }
+
+    fn show_cursor(&mut self) -> Result<(), io::Error> {
+        Ok(())
+    }
+
+    fn clear(&mut self) -> Result<(), io::Error> {
+        for symbol in &mut self.symbols {
+            *symbol = String::from(" ");
+        }
+        Ok(())
+    }
+
+    fn size(&self) -> Result<Rect, io::Error> {
+        Ok(Rect::new(0, 0, self.width, self.height))
+    }
+
+    fn flush(&mut self) -> Result<(), io::Error> {
+        Ok(())
+    }
+}
@@ -0,0 +1,305 @@
+use std::io::{self, Write};
+
+use chrono::prelude::*;
+use serde_json;
+
+use app::{Action, App, Screen};
+use command::{required, Command, RoomTarget};
+use game::{Entity, Game, GameInformation, Message, Room};
+use net::Status;
+use plain::Output;
+use protocol::ServerMessage;
+
+/// One line of output in JSON mode, tagged with `"event"`.
+#[derive(Serialize)]
+#[serde(tag = "event", rename_all = "lowercase")]
+enum Update<'a> {
+    /// The first line, before anything else happens.
+    Hello {
+        game: &'a Game,
+        /// Whether a `character` command is expected before `start`.
+        needs_character: bool,
+    },
+    Status {
+        status: String,
+    },
+    Game {
+        game: &'a Game,
+    },
+    Message {
+        message: &'a Message,
+        whisper: bool,
+    },
+    /// Something the client itself had to say, like a command that
+    /// couldn't be carried out.
+    Notice {
+        text: &'a str,
+        at: &'a DateTime<Local>,
+    },
+    Room {
+        room: &'a Room,
+    },
+    Exit {
+        room: &'a Room,
+    },
+    Entity {
+        entity: &'a Entity,
+        player: bool,
+    },
+    Error {
+        code: u8,
+        title: &'static str,
+        message: &'a str,
+        /// The command it was most likely a reply to.
+        command: Option<&'a str>,
+    },
+    Accept {
+        message_type: u8,
+    },
+}
+
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum Target {
+    Number(u16),
+    Name(String),
+}
+
+impl From<Target> for RoomTarget {
+    fn from(target: Target) -> RoomTarget {
+        match target {
+            Target::Number(number) => RoomTarget::Number(number),
+            Target::Name(name) => RoomTarget::Name(name),
+        }
+    }
+}
+
+/// A command read from stdin, tagged with `"command"`. These mirror the
+/// slash commands that act on the game.
+#[derive(Deserialize)]
+#[serde(tag = "command", rename_all = "lowercase")]
+enum Request {
+    Character {
+        name: String,
+        attack: u16,
+        defense: u16,
+        regen: u16,
+        #[serde(default)]
+        description: String,
+    },
+    Go {
+        room: Target,
+    },
+    Travel {
+        room: Target,
+    },
+    Fight,
+    Pvp {
+        target: String,
+    },
+    Loot {
+        target: String,
+    },
+    Start,
+    Say {
+        text: String,
+    },
+    Tell {
+        to: String,
+        text: String,
+    },
+    Reply {
+        text: String,
+    },
+    Errors,
+    Leave,
+    Quit,
+}
+
+impl Request {
+    /// The command this asks for, held to the same rules as a typed one.
+    fn into_command(self) -> Result<Command, String> {
+        Ok(match self {
+            Request::Character {
+                name,
+                attack,
+                defense,
+                regen,
+                description,
+            } => Command::Character(Entity {
+                name,
+                join_battle: true,
+                attack,
+                defense,
+                regen,
+                description,
+                ..Entity::default()
+            }),
+            Request::Go { room } => Command::Go(room.into()),
+            Request::Travel { room } => Command::Travel(room.into()),
+            Request::Fight => Command::Fight,
+            Request::Pvp { target } => {
+                Command::Pvp(required(&target, r#"{"command": "pvp", "target": <name>}"#)?)
+            }
+            Request::Loot { target } => {
+                Command::Loot(required(&target, r#"{"command": "loot", "target": <name>}"#)?)
+            }
+            Request::Start => Command::Start,
+            Request::Say { text } => {
+                Command::Say(required(&text, r#"{"command": "say", "text": <text>}"#)?)
+            }
+            Request::Tell { to, text } => {
+                let usage = r#"{"command": "tell", "to": <name>, "text": <text>}"#;
+                Command::Tell(required(&to, usage)?, required(&text, usage)?)
+            }
+            Request::Reply { text } => {
+                Command::Reply(required(&text, r#"{"command": "reply", "text": <text>}"#)?)
+            }
+            Request::Errors => Command::Errors,
+            Request::Leave => Command::Leave,
+            Request::Quit => Command::Quit,
+        })
+    }
+}
+
+fn parse(line: &str) -> Result<Command, String> {
+    serde_json::from_str::<Request>(line)
+        .map_err(|e| format!("Invalid command: {}", e))?
+        .into_command()
+}
+
+/// Writes every change to the game as one JSON object per line, and reads
+/// commands the same way.
+#[derive(Default)]
+pub struct Emitter {
+    /// How many of the feed's messages have been written.
+    emitted: usize,
+}
+
+impl Emitter {
+    fn emit(&self, update: &Update) -> io::Result<()> {
+        let stdout = io::stdout();
+        let mut stdout = stdout.lock();
+        serde_json::to_writer(&mut stdout, update)?;
+        writeln!(stdout)
+    }
+}
+
+impl Output for Emitter {
+    fn introduce(&mut self, app: &App, game_info: &GameInformation) -> io::Result<()> {
+        self.status(&app.connection_status)?;
+        let needs_character = match app.screen {
+            Screen::CreateCharacter(_) => true,
+            Screen::Main => false,
+        };
+        self.emit(&Update::Hello {
+            game: &game_info.game,
+            needs_character,
+        })
+    }
+
+    fn messages(&mut self, game_info: &GameInformation) -> io::Result<()> {
+        for msg in game_info.messages_since(self.emitted) {
+            let update = if msg.notice {
+                Update::Notice {
+                    text: &msg.content,
+                    at: &msg.received,
+                }
+            } else {
+                Update::Message {
+                    message: msg,
+                    whisper: game_info.is_whisper(msg),
+                }
+            };
+            self.emit(&update)?;
+        }
+        self.emitted = game_info.message_total;
+        Ok(())
+    }
+
+    fn packet(&mut self, msg: &ServerMessage, game_info: &GameInformation) -> io::Result<()> {
+        match *msg {
+            ServerMessage::Game(ref game) => self.emit(&Update::Game { game }),
+            ServerMessage::Room(ref room) => self.emit(&Update::Room { room }),
+            ServerMessage::Connection(ref room) => self.emit(&Update::Exit { room }),
+            ServerMessage::Character(ref entity) => self.emit(&Update::Entity {
+                entity,
+                player: entity.name == game_info.player.name,
+            }),
+            ServerMessage::Error { code, .. } => match game_info.notifications.history.back() {
+                Some(error) => self.emit(&Update::Error {
+                    code,
+                    title: error.code.title(),
+                    message: &error.message,
+                    command: error.command.as_deref(),
+                }),
+                None => Ok(()),
+            },
+            ServerMessage::Accept(message_type) => self.emit(&Update::Accept { message_type }),
+            ServerMessage::Message(_) | ServerMessage::Version { .. } => Ok(()),
+        }
+    }
+
+    fn status(&mut self, status: &Status) -> io::Result<()> {
+        self.emit(&Update::Status {
+            status: status.describe(Local::now()),
+        })
+    }
+
+    fn read(&self, line: String) -> Option<Action> {
+        if line.trim().is_empty() {
+            return None;
+        }
+        Some(match parse(&line) {
+            Ok(command) => Action::Execute(command),
+            Err(e) => Action::Notice(e),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::parse;
+    use command::{Command, RoomTarget};
+
+    #[test]
+    fn commands_are_read_from_json() {
+        match parse(r#"{"command": "go", "room": 2}"#) {
+            Ok(Command::Go(RoomTarget::Number(2))) => {}
+            _ => panic!("Expected /go 2."),
+        }
+        match parse(r#"{"command": "travel", "room": "Great Hall"}"#) {
+            Ok(Command::Travel(RoomTarget::Name(ref name))) if name == "Great Hall" => {}
+            _ => panic!("Expected /travel Great Hall."),
+        }
+        match parse(r#"{"command": "tell", "to": "Bob", "text": "hi"}"#) {
+            Ok(Command::Tell(ref to, ref text)) if to == "Bob" && text == "hi" => {}
+            _ => panic!("Expected /tell Bob hi."),
+        }
+        match parse(r#"{"command": "character", "name": "Alice", "attack": 60, "defense": 20, "regen": 20}"#) {
+            Ok(Command::Character(ref entity)) => {
+                assert_eq!(entity.name, "Alice");
+                assert_eq!(entity.attack, 60);
+                assert!(entity.description.is_empty());
+            }
+            _ => panic!("Expected a character."),
+        }
+        match parse(r#"{"command": "fight"}"#) {
+            Ok(Command::Fight) => {}
+            _ => panic!("Expected /fight."),
+        }
+    }
+
+    #[test]
+    fn bad_commands_are_explained() {
+        assert!(parse("/go 2").is_err());
+        assert!(parse(r#"{"command": "dance"}"#).is_err());
+        assert!(parse(r#"{"command": "go"}"#).err().unwrap().starts_with("Invalid command: "));
+        assert_eq!(
+            parse(r#"{"command": "say", "text": "  "}"#).err(),
+            Some(String::from(r#"Usage: {"command": "say", "text": <text>}"#))
+        );
+        assert!(parse(r#"{"command": "tell", "to": "", "text": "hi"}"#).is_err());
+        assert!(parse(r#"{"command": "reply", "text": ""}"#).is_err());
+    }
+}
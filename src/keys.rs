@@ -0,0 +1,196 @@
+use termion::event::Key;
+
+// What `App::handle_key` uses for the input line and for picking exits and
+// map lines, which no shortcut may take over.
+const RESERVED: [Key; 12] = [
+    Key::Char('\n'),
+    Key::Up,
+    Key::Down,
+    Key::Left,
+    Key::Right,
+    Key::Home,
+    Key::End,
+    Key::Backspace,
+    Key::Delete,
+    Key::Ctrl('u'),
+    Key::Ctrl('a'),
+    Key::Ctrl('e'),
+];
+
+/// Keys for the main screen's shortcuts. Editing keys for the input line
+/// are fixed.
+#[derive(Clone, PartialEq)]
+pub struct KeyBindings {
+    pub quit: Key,
+    pub cycle_focus: Key,
+    pub room_tab: Key,
+    pub combat_tab: Key,
+    pub map_tab: Key,
+    pub scroll_up: Key,
+    pub scroll_down: Key,
+    pub stop_travel: Key,
+    pub next_theme: Key,
+}
+
+impl Default for KeyBindings {
+    fn default() -> KeyBindings {
+        KeyBindings {
+            quit: Key::Ctrl('c'),
+            cycle_focus: Key::Char('\t'),
+            room_tab: Key::F(1),
+            combat_tab: Key::F(2),
+            map_tab: Key::F(3),
+            scroll_up: Key::PageUp,
+            scroll_down: Key::PageDown,
+            stop_travel: Key::Esc,
+            next_theme: Key::F(4),
+        }
+    }
+}
+
+impl KeyBindings {
+    /// Every shortcut with its name in the config file.
+    fn named(&self) -> [(&'static str, Key); 9] {
+        [
+            ("quit", self.quit),
+            ("cycle_focus", self.cycle_focus),
+            ("room_tab", self.room_tab),
+            ("combat_tab", self.combat_tab),
+            ("map_tab", self.map_tab),
+            ("scroll_up", self.scroll_up),
+            ("scroll_down", self.scroll_down),
+            ("stop_travel", self.stop_travel),
+            ("next_theme", self.next_theme),
+        ]
+    }
+
+    /// Shortcuts are checked before the input line sees a key, so a plain
+    /// character would stop it from being typed, an editing key would stop
+    /// it from being edited, and a key bound twice only ever does the first
+    /// thing.
+    pub fn validate(&self) -> Result<(), String> {
+        let named = self.named();
+        for (i, &(name, key)) in named.iter().enumerate() {
+            if let Key::Char(c) = key {
+                if !c.is_control() {
+                    return Err(format!(
+                        "keys: {} can't be '{}', it's needed for typing. Try ctrl-{} or alt-{}.",
+                        name, c, c, c
+                    ));
+                }
+            }
+            if RESERVED.contains(&key) {
+                return Err(format!(
+                    "keys: {} can't be {}, it's needed for editing and selecting.",
+                    name,
+                    key_name(key)
+                ));
+            }
+            if let Some(&(other, _)) = named[..i].iter().find(|&&(_, bound)| bound == key) {
+                return Err(format!("keys: {} and {} are both {}.", other, name, key_name(key)));
+            }
+        }
+        Ok(())
+    }
+
+    /// Titles for the info section's tabs, in the same order as `InfoTab`.
+    pub fn tab_titles(&self) -> [String; 3] {
+        [
+            format!("Room ({})", key_name(self.room_tab)),
+            format!("Combat ({})", key_name(self.combat_tab)),
+            format!("Map ({})", key_name(self.map_tab)),
+        ]
+    }
+
+    /// The shortcuts as listed by `/help`.
+    pub fn help(&self) -> String {
+        format!(
+            "Keys: {} cycles focus, {} room, {} combat, {} map, {}/{} scroll the feed, \
+             {} stops travelling, {} switches theme, {} quits.",
+            key_name(self.cycle_focus),
+            key_name(self.room_tab),
+            key_name(self.combat_tab),
+            key_name(self.map_tab),
+            key_name(self.scroll_up),
+            key_name(self.scroll_down),
+            key_name(self.stop_travel),
+            key_name(self.next_theme),
+            key_name(self.quit)
+        )
+    }
+}
+
+/// Reads a key as written in the config file, such as `f2`, `ctrl-x`,
+/// `pageup` or a single character.
+pub fn parse_key(name: &str) -> Result<Key, String> {
+    let lower = name.trim().to_lowercase();
+    let single = |rest: &str| {
+        let mut chars = rest.chars();
+        match (chars.next(), chars.next()) {
+            (Some(c), None) => Some(c),
+            _ => None,
+        }
+    };
+
+    if let Some(c) = lower.strip_prefix("ctrl-").and_then(single) {
+        return Ok(Key::Ctrl(c));
+    }
+    if let Some(c) = lower.strip_prefix("alt-").and_then(single) {
+        return Ok(Key::Alt(c));
+    }
+    if let Some(Ok(n)) = lower.strip_prefix('f').map(str::parse::<u8>) {
+        if (1..=12).contains(&n) {
+            return Ok(Key::F(n));
+        }
+    }
+
+    Ok(match lower.as_str() {
+        "tab" => Key::Char('\t'),
+        "backtab" | "shift-tab" => Key::BackTab,
+        "enter" | "return" => Key::Char('\n'),
+        "esc" | "escape" => Key::Esc,
+        "space" => Key::Char(' '),
+        "backspace" => Key::Backspace,
+        "delete" | "del" => Key::Delete,
+        "insert" | "ins" => Key::Insert,
+        "home" => Key::Home,
+        "end" => Key::End,
+        "pageup" | "pgup" => Key::PageUp,
+        "pagedown" | "pgdn" => Key::PageDown,
+        "up" => Key::Up,
+        "down" => Key::Down,
+        "left" => Key::Left,
+        "right" => Key::Right,
+        // Characters are case sensitive, so take them as written.
+        _ => match single(name.trim()) {
+            Some(c) => Key::Char(c),
+            None => return Err(format!("Unknown key '{}'.", name)),
+        },
+    })
+}
+
+pub fn key_name(key: Key) -> String {
+    match key {
+        Key::Char('\t') => String::from("Tab"),
+        Key::Char('\n') => String::from("Enter"),
+        Key::Char(' ') => String::from("Space"),
+        Key::Char(c) => c.to_string(),
+        Key::Ctrl(c) => format!("Ctrl-{}", c),
+        Key::Alt(c) => format!("Alt-{}", c),
+        Key::F(n) => format!("F{}", n),
+        Key::BackTab => String::from("Shift-Tab"),
+        Key::Esc => String::from("Esc"),
+        Key::Backspace => String::from("Backspace"),
+        Key::Delete => String::from("Delete"),
+        Key::Insert => String::from("Insert"),
+        Key::Home => String::from("Home"),
+        Key::End => String::from("End"),
+        Key::PageUp => String::from("PgUp"),
+        Key::PageDown => String::from("PgDn"),
+        Key::Up => String::from("Up"),
+        Key::Down => String::from("Down"),
+        Key::Left => String::from("Left"),
+        Key::Right => String::from("Right"),
+        _ => String::from("?"),
+    }
+}
@@ -1,32 +1,81 @@
 extern crate chrono;
+extern crate dirs;
+extern crate libc;
 extern crate liblurk;
+extern crate signal_hook;
 extern crate termion;
 extern crate tui;
+extern crate serde;
+#[macro_use]
+extern crate serde_derive;
+extern crate toml;
+extern crate serde_json;
+extern crate rhai;
 
 mod ui;
 mod game;
+#[cfg(test)]
+mod headless;
 mod app;
+mod character;
+mod cli;
+mod combat;
+mod command;
+mod config;
+mod error;
+mod events;
+mod input;
+mod json;
+mod keys;
+mod map;
+mod net;
+mod notification;
+mod plain;
+mod protocol;
+mod recording;
+mod restore;
+mod script;
+#[cfg(test)]
+mod server;
+mod theme;
+mod travel;
 
-use std::io;
-use termion::event;
-use termion::input::TermRead;
+use chrono::prelude::*;
+use std::process;
+use std::time::{Duration, Instant};
+use termion::event::{Event as InputEvent, MouseButton, MouseEvent};
+use tui::backend::MouseBackend;
 use std::sync::*;
 
+use app::{Action, App, FeedView, Screen};
+use character::{CharacterForm, FormStatus};
+use command::Command;
+use events::{Event, Events};
 use game::*;
+use protocol::{ClientMessage, CHARACTER_TYPE};
+use travel::Journey;
 
 use std::collections::VecDeque;
 
-fn main() {
+// Often enough to keep the clock's seconds current.
+const TICK_RATE_MS: u64 = 250;
+
+/// Offline stand-in used when no server is given on the command line.
+fn mock_game_information() -> GameInformation {
     let mock_msgs = VecDeque::from(vec![
         Message {
             sender: String::from("A"),
             receiver: String::new(),
             content: String::from("Hello, world"),
+            received: Local::now(),
+            notice: false,
         },
         Message {
             sender: String::from("B"),
             receiver: String::new(),
             content: String::from("World, hello"),
+            received: Local::now(),
+            notice: false,
         },
     ]);
 
@@ -113,36 +162,1024 @@ fn main() {
         },
     ];
 
-    let game_info = Arc::new(Mutex::new(GameInformation {
-        messages: mock_msgs,
-        player: mock_player,
-        current_room: mock_room,
-        adjacent_rooms: mock_connections,
-        current_enemies: mock_enemies,
-        game: Game {
-            stat_limit: 100,
-            init_points: 100,
-            description: String::from("Mock game description."),
+    let mut game_info = GameInformation::new(Game {
+        stat_limit: 100,
+        init_points: 100,
+        description: String::from("Mock game description."),
+    });
+    game_info.messages = mock_msgs;
+    game_info.message_total = game_info.messages.len();
+    game_info.player = mock_player;
+    game_info.map.visit(&mock_room);
+    for connection in &mock_connections {
+        game_info.map.connect(mock_room.number, connection);
+    }
+    game_info.current_room = mock_room;
+    game_info.adjacent_rooms = mock_connections;
+    game_info.current_enemies = mock_enemies;
+    game_info
+}
+
+fn connect(
+    host: &str,
+    port: u16,
+    recorder: Option<Arc<recording::Recorder>>,
+) -> error::Result<(net::Connection, GameInformation)> {
+    let (connection, game) = net::join(host, port, recorder)?;
+    Ok((connection, GameInformation::new(game)))
+}
+
+/// Carries on over a new connection where the old one left off. The same
+/// character is sent again, and started again if it was in play.
+fn resume(
+    connection: &net::Connection,
+    game: Game,
+    app: &App,
+    game_data: &Arc<Mutex<GameInformation>>,
+    notify: mpsc::Sender<Event>,
+) -> error::Result<()> {
+    game_data.lock().expect("Failed to lock game data.").game = game;
+    connection.spawn_reader(game_data.clone(), notify)?;
+    if let Ok(handle) = connection.try_clone() {
+        restore::register_connection(handle);
+    }
+
+    let entity = match (&app.screen, &app.character) {
+        (Screen::Main, Some(entity)) => entity.clone(),
+        _ => return Ok(()),
+    };
+    game_data
+        .lock()
+        .expect("Failed to lock game data.")
+        .notifications
+        .sent(format!("rejoining as {}", entity.name), CHARACTER_TYPE);
+    connection.send(&ClientMessage::Character(entity))?;
+    if app.started {
+        connection.send(&ClientMessage::Start)?;
+    }
+    Ok(())
+}
+
+/// Acts on a line submitted from the input section.
+fn dispatch(
+    line: String,
+    app: &mut App,
+    game_data: &Arc<Mutex<GameInformation>>,
+    connection: Option<&net::Connection>,
+) {
+    match command::parse(&line) {
+        Ok(command) => execute(command, app, game_data, connection),
+        Err(e) => game_data.lock().expect("Failed to lock game data.").notice(e),
+    }
+}
+
+fn execute(
+    command: Command,
+    app: &mut App,
+    game_data: &Arc<Mutex<GameInformation>>,
+    connection: Option<&net::Connection>,
+) {
+    let mut game_info = game_data.lock().expect("Failed to lock game data.");
+
+    match command {
+        Command::Quit => {
+            app.should_quit = true;
+            return;
+        }
+        Command::Help => {
+            game_info.notice(String::from(command::HELP));
+            return game_info.notice(app.keys.help());
+        }
+        Command::Conversation(name) => {
+            app.feed_view = match name {
+                Some(name) => FeedView::Conversation(name),
+                None => FeedView::All,
+            };
+            app.feed_scroll = 0;
+            return;
+        }
+        Command::Theme(None) => {
+            return game_info.notice(format!(
+                "Theme: {}. Themes: {}.",
+                app.theme_name,
+                theme::PRESETS.join(", ")
+            ))
+        }
+        Command::Theme(Some(name)) => {
+            return game_info.notice(match app.set_theme(&name) {
+                Ok(()) => format!("Theme: {}", app.theme_name),
+                Err(e) => e,
+            })
+        }
+        Command::Errors => {
+            let history: Vec<String> = game_info
+                .notifications
+                .history
+                .iter()
+                .map(|error| format!("{} {}", error.received.format("%H:%M:%S"), error.summary()))
+                .collect();
+            if history.is_empty() {
+                return game_info.notice(String::from("The server hasn't sent any errors."));
+            }
+            for line in history {
+                game_info.notice(line);
+            }
+            return;
+        }
+        Command::Reload => {
+            app.reload_scripts = true;
+            return;
+        }
+        Command::Character(entity) => {
+            if let Err(e) = character::validate(&entity, &game_info.game) {
+                return game_info.notice(e);
+            }
+            if connection.is_none() {
+                return game_info.notice(String::from("Not connected to a server."));
+            }
+            drop(game_info);
+            return send_character(entity, app, game_data, connection);
+        }
+        _ => {}
+    }
+
+    let mut journey = None;
+    let messages = match command {
+        Command::Travel(ref target) => match Journey::plan(&game_info, target) {
+            Ok(planned) => {
+                let first = planned.next_step();
+                journey = Some(planned);
+                first.map(ClientMessage::ChangeRoom).into_iter().collect()
+            }
+            Err(e) => return game_info.notice(e),
+        },
+        _ => match command.to_messages(&game_info) {
+            Ok(messages) => messages,
+            Err(e) => return game_info.notice(e),
+        },
+    };
+
+    let connection = match connection {
+        Some(connection) => connection,
+        None => return game_info.notice(String::from("Not connected to a server.")),
+    };
+
+    for msg in &messages {
+        if let Err(e) = connection.send(msg) {
+            app.travel = None;
+            return game_info.notice(e.report());
+        }
+    }
+    if let Some(last) = messages.last() {
+        game_info.notifications.sent(command.to_string(), last.type_id());
+    }
+
+    if let Some(journey) = journey {
+        game_info.notice(format!(
+            "Travelling to {}, {} room(s) away. Press Esc to stop.",
+            journey.destination,
+            journey.remaining()
+        ));
+        app.travel = Some(journey);
+    }
+
+    if let Some(echo) = command.echo(&game_info) {
+        connection.record_echo(&echo);
+        game_info.push_message(echo);
+    }
+
+    if let Command::Fight | Command::Pvp(_) = command {
+        game_info.combat.begin_fight();
+    }
+
+    match command {
+        Command::Start => app.started = true,
+        Command::Leave => app.should_quit = true,
+        _ => {}
+    }
+}
+
+/// Sends the character from the creation form. The verdict arrives later as
+/// an ACCEPT or ERROR through the event loop.
+fn send_character(
+    entity: Entity,
+    app: &mut App,
+    game_data: &Arc<Mutex<GameInformation>>,
+    connection: Option<&net::Connection>,
+) {
+    let connection = match connection {
+        Some(connection) => connection,
+        None => return app.character_rejected(String::from("Not connected to a server.")),
+    };
+
+    {
+        let mut game_info = game_data.lock().expect("Failed to lock game data.");
+        // The reader recognises updates to our own character by name.
+        game_info.player = entity.clone();
+        game_info.notifications.sent(format!("creating {}", entity.name), CHARACTER_TYPE);
+    }
+    app.character = Some(entity.clone());
+    if let Screen::CreateCharacter(ref mut form) = app.screen {
+        form.status = FormStatus::Waiting(Instant::now());
+    }
+
+    if let Err(e) = connection.send(&ClientMessage::Character(entity)) {
+        app.character_rejected(e.report());
+    }
+}
+
+fn perform(
+    action: Action,
+    app: &mut App,
+    game_data: &Arc<Mutex<GameInformation>>,
+    connection: Option<&net::Connection>,
+) {
+    match action {
+        Action::Submit(line) => dispatch(line, app, game_data, connection),
+        Action::Execute(command) => execute(command, app, game_data, connection),
+        Action::SendCharacter(entity) => send_character(entity, app, game_data, connection),
+        Action::Notice(content) => {
+            game_data.lock().expect("Failed to lock game data.").notice(content)
+        }
+    }
+}
+
+/// What both front ends drive: the UI state, the game and the link to the
+/// server.
+struct Client {
+    app: App,
+    game_data: Arc<Mutex<GameInformation>>,
+    connection: Option<net::Connection>,
+    /// Where to reconnect to if the connection drops.
+    server: Option<(String, u16)>,
+    recorder: Option<Arc<recording::Recorder>>,
+    notify: mpsc::Sender<Event>,
+    scripts: script::Scripts,
+}
+
+impl Client {
+    fn perform(&mut self, action: Action) {
+        perform(action, &mut self.app, &self.game_data, self.connection.as_ref());
+    }
+
+    /// Loads the scripts afresh and carries out whatever they ask for as
+    /// they run. A `/reload` among that is dropped, or they would be loaded
+    /// over and over.
+    fn load_scripts(&mut self) {
+        for action in self.scripts.load() {
+            self.perform(action);
+        }
+        self.app.reload_scripts = false;
+    }
+
+    /// Carries out a `/reload` once whatever asked for it has finished.
+    fn reload_if_asked(&mut self) {
+        if self.app.reload_scripts {
+            self.load_scripts();
+            let summary = self.scripts.summary();
+            self.notice(summary);
+        }
+    }
+
+    fn notice(&self, content: String) {
+        self.game_data.lock().expect("Failed to lock game data.").notice(content);
+    }
+
+    /// Reacts to everything but input and resizing, which are up to the
+    /// front end.
+    fn handle(&mut self, evt: Event) {
+        match evt {
+            Event::Tick => self.app.tick(),
+            Event::Server(msg) => {
+                let action = {
+                    let mut game_info = self.game_data.lock().expect("Failed to lock game data.");
+                    self.app.handle_server_message(&msg, &mut game_info)
+                };
+                if let Some(action) = action {
+                    self.perform(action);
+                }
+
+                let reactions = {
+                    let game_info = self.game_data.lock().expect("Failed to lock game data.");
+                    self.scripts.react(&msg, &game_info)
+                };
+                for action in reactions {
+                    self.perform(action);
+                }
+            }
+            Event::Disconnected(e) => {
+                self.notice(format!("Lost connection to server. ({})", e.report()));
+                restore::forget_connection();
+                self.connection = None;
+                self.app.travel = None;
+                if let Some((ref host, port)) = self.server {
+                    let retry = net::spawn_reconnect(
+                        host.clone(),
+                        port,
+                        self.recorder.clone(),
+                        self.game_data.clone(),
+                        self.notify.clone(),
+                    );
+                    if let Err(e) = retry {
+                        self.notice(e.report());
+                    }
+                }
+            }
+            Event::Status(status) => self.app.connection_status = status,
+            Event::Reconnected(connection, game) => {
+                let resumed = resume(
+                    &connection,
+                    game,
+                    &self.app,
+                    &self.game_data,
+                    self.notify.clone(),
+                );
+                self.notice(match resumed {
+                    Ok(()) => String::from("Reconnected."),
+                    Err(e) => e.report(),
+                });
+                if let Some((ref host, port)) = self.server {
+                    self.app.connection_status =
+                        net::Status::Connected(format!("{}:{}", host, port));
+                }
+                self.connection = Some(connection);
+            }
+            Event::Input(_) | Event::Line(_) | Event::EndOfInput | Event::Resize => {}
+        }
+
+        if let Some(e) = self.recorder.as_ref().and_then(|recorder| recorder.take_error()) {
+            self.notice(e.report());
+        }
+    }
+}
+
+fn handle_input(
+    evt: InputEvent,
+    client: &mut Client,
+    user_interface: &ui::TerminalInterface<MouseBackend>,
+) {
+    let action = {
+        let game_info = client.game_data.lock().expect("Failed to lock game data.");
+        if let InputEvent::Mouse(MouseEvent::Press(MouseButton::Left, x, y)) = evt {
+            if let Some(index) = user_interface.room_at(x, y) {
+                client.app.select_room(index, &game_info);
+            }
+        }
+        client.app.handle_event(evt, &game_info)
+    };
+
+    if let Some(action) = action {
+        client.perform(action);
+    }
+}
+
+/// The full screen interface.
+fn run_tui(mut client: Client, events: Events) {
+    restore::save_terminal_state();
+    let mut user_interface = ui::TerminalInterface::new(client.game_data.clone())
+        .unwrap_or_else(|e| {
+            restore::restore_terminal();
+            eprintln!("{}", e.report());
+            process::exit(1);
+        });
+
+    let mut result = user_interface.render(&client.app);
+    let mut last_error = None;
+    let mut fatal = None;
+
+    loop {
+        match result {
+            Ok(()) => {
+                client.app.feed_layout = user_interface.feed_layout();
+                last_error = None;
+            }
+            // Every event redraws the whole screen, so a frame that didn't
+            // make it is soon replaced. Only say so once while it keeps
+            // failing the same way.
+            Err(ref e) if e.is_recoverable() => {
+                let report = e.report();
+                if last_error.as_ref() != Some(&report) {
+                    client.notice(report.clone());
+                }
+                last_error = Some(report);
+            }
+            Err(e) => {
+                fatal = Some(e);
+                break;
+            }
+        }
+
+        let evt = match events.next() {
+            Ok(evt) => evt,
+            Err(_) => break,
+        };
+
+        let mut resized = Ok(());
+        match evt {
+            Event::Input(evt) => handle_input(evt, &mut client, &user_interface),
+            Event::Resize => resized = user_interface.update(),
+            evt => client.handle(evt),
+        }
+        client.reload_if_asked();
+
+        if client.app.should_quit {
+            break;
+        }
+
+        result = resized.and_then(|()| user_interface.render(&client.app));
+    }
+
+    // The terminal has to be back to normal before the reason can be read.
+    drop(user_interface);
+    if let Some(e) = fatal {
+        restore::restore_terminal();
+        eprintln!("{}", e.report());
+        process::exit(1);
+    }
+}
+
+/// Line mode: commands are read from stdin a line at a time and everything
+/// that happens is written to stdout as it does, in whatever form `output`
+/// writes it.
+fn run_lines(mut client: Client, events: Events, output: &mut dyn plain::Output) {
+    {
+        let game_info = client.game_data.lock().expect("Failed to lock game data.");
+        if output.introduce(&client.app, &game_info).is_err() {
+            return;
+        }
+    }
+
+    while let Ok(evt) = events.next() {
+        let mut written = Ok(());
+        match evt {
+            Event::Line(line) => {
+                if let Some(action) = output.read(line) {
+                    client.perform(action);
+                }
+            }
+            Event::EndOfInput => break,
+            Event::Server(msg) => {
+                {
+                    let game_info = client.game_data.lock().expect("Failed to lock game data.");
+                    written = output
+                        .messages(&game_info)
+                        .and_then(|()| output.packet(&msg, &game_info));
+                }
+                client.handle(Event::Server(msg));
+            }
+            Event::Status(status) => {
+                written = output.status(&status);
+                client.handle(Event::Status(status));
+            }
+            evt => client.handle(evt),
+        }
+        client.reload_if_asked();
+
+        let written = written.and_then(|()| {
+            output.messages(&client.game_data.lock().expect("Failed to lock game data."))
+        });
+        // Whoever was reading has gone away.
+        if written.is_err() || client.app.should_quit {
+            break;
+        }
+    }
+}
+
+fn main() {
+    let options = cli::Options::from_args().unwrap_or_else(|e| {
+        eprintln!("{}\n{}", e, cli::USAGE);
+        process::exit(1);
+    });
+
+    let config = config::Config::load(options.config.as_deref()).unwrap_or_else(|e| {
+        eprintln!("{}", e.report());
+        process::exit(1);
+    });
+    let scripts_dir = script::scripts_dir(&config);
+
+    let recorder = match options.record {
+        Some(ref path) => match recording::Recorder::create(path) {
+            Ok(recorder) => Some(Arc::new(recorder)),
+            Err(e) => {
+                eprintln!("{}", e.report());
+                process::exit(1);
+            }
         },
-    }));
+        None => None,
+    };
 
-    let mut user_interface = ui::TerminalInterface::new(game_info.clone()).unwrap();
+    let replay = options.replay.as_ref().map(|path| {
+        let entries = recording::load(path).unwrap_or_else(|e| {
+            eprintln!("{}", e.report());
+            process::exit(1);
+        });
+        (path.clone(), entries)
+    });
 
-    let stdin = io::stdin();
+    // The connection is held for the lifetime of the client so the server
+    // doesn't see us hang up. A replay stands in for the server.
+    let server = match replay {
+        Some(_) => None,
+        None => options.server.or_else(|| config.server.clone()),
+    };
+    let (connection, game_info) = match server {
+        Some((ref host, port)) => {
+            let (connection, game_info) =
+                connect(host, port, recorder.clone()).unwrap_or_else(|e| {
+                    eprintln!("{}", e.report());
+                    process::exit(1);
+                });
+            (Some(connection), game_info)
+        }
+        None if replay.is_some() => (None, GameInformation::new(Game::default())),
+        None => (None, mock_game_information()),
+    };
 
-    user_interface.render().expect("Failed to render.");
+    let mut game_info = game_info;
+    if let Some(capacity) = options.feed_capacity.or(config.feed_capacity) {
+        game_info.message_capacity = capacity;
+    }
+    let game_info = Arc::new(Mutex::new(game_info));
 
-    for c in stdin.keys() {
-        user_interface.render().expect("Failed to render");
-        match c {
-            Ok(evt) => {
-                if evt == event::Key::Char('q') {
-                    break;
+    let tick_rate = Duration::from_millis(TICK_RATE_MS);
+    let events = if options.plain || options.json {
+        Events::lines(tick_rate)
+    } else {
+        Events::new(tick_rate)
+    };
+
+    if let Some(ref connection) = connection {
+        connection.spawn_reader(game_info.clone(), events.sender()).unwrap_or_else(|e| {
+            eprintln!("{}", e.report());
+            process::exit(1);
+        });
+    }
+
+    if let Some(ref connection) = connection {
+        match connection.try_clone() {
+            Ok(handle) => restore::register_connection(handle),
+            Err(e) => eprintln!("{}", e.report()),
+        }
+    }
+
+    if let Some((path, entries)) = replay {
+        let replaying = recording::spawn_replay(
+            entries,
+            path,
+            options.speed,
+            game_info.clone(),
+            events.sender(),
+        );
+        if let Err(e) = replaying {
+            eprintln!("{}", e.report());
+            process::exit(1);
+        }
+    }
+
+    restore::install_panic_hook();
+    restore::install_signal_handlers().unwrap_or_else(|e| {
+        eprintln!("{}", e.report());
+        process::exit(1);
+    });
+
+    // The mock data already has a character, a real server needs one made.
+    let mut app = App::new(if connection.is_some() {
+        Screen::CreateCharacter(CharacterForm::new())
+    } else {
+        Screen::Main
+    });
+    app.theme_name = config.theme_name;
+    app.theme = config.theme;
+    app.panels = config.panels;
+    app.keys = config.keys;
+    if let Some((ref host, port)) = server {
+        app.connection_status = net::Status::Connected(format!("{}:{}", host, port));
+    }
+    if let Some(ref path) = options.replay {
+        app.connection_status = net::Status::Replaying {
+            path: path.clone(),
+            speed: options.speed,
+            finished: false,
+        };
+    }
+
+    let mut client = Client {
+        app,
+        game_data: game_info,
+        connection,
+        server,
+        recorder,
+        notify: events.sender(),
+        scripts: script::Scripts::new(scripts_dir),
+    };
+    // A replay can't be acted on, so scripts would only get in the way.
+    if options.replay.is_none() {
+        client.load_scripts();
+        if !client.scripts.is_empty() {
+            let summary = client.scripts.summary();
+            client.notice(summary);
+        }
+    }
+    if options.plain {
+        run_lines(client, events, &mut plain::Printer::default());
+    } else if options.json {
+        run_lines(client, events, &mut json::Emitter::default());
+    } else {
+        run_tui(client, events);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::mpsc::{self, Receiver};
+    use std::env;
+    use std::fs;
+    use std::process;
+    use std::sync::*;
+    use std::thread;
+    use std::time::{Duration, Instant};
+
+    use app::{Action, App, Screen};
+    use character::{CharacterForm, FormStatus};
+    use events::Event;
+    use game::*;
+    use net;
+    use notification::ErrorCode;
+    use protocol::{ClientMessage, ServerMessage, CHARACTER_TYPE};
+    use recording::{self, Recorder};
+    use server::{Server, World};
+    use script::Scripts;
+    use super::{connect, perform, resume, Client};
+
+    // These run the client against the mock server in `server.rs`, on
+    // localhost and without a terminal.
+
+    const REPLY_TIMEOUT_SECS: u64 = 5;
+
+    fn start_server() -> u16 {
+        let server = Server::bind(("127.0.0.1", 0), World::example()).unwrap();
+        let port = server.local_addr().unwrap().port();
+        thread::spawn(move || server.run());
+        port
+    }
+
+    fn character(name: &str) -> Entity {
+        Entity {
+            name: String::from(name),
+            attack: 60,
+            defense: 20,
+            regen: 20,
+            description: format!("{} is only here for the tests.", name),
+            ..Entity::default()
+        }
+    }
+
+    /// A client with everything but the terminal.
+    struct Session {
+        app: App,
+        game_data: Arc<Mutex<GameInformation>>,
+        connection: net::Connection,
+        events: Receiver<Event>,
+    }
+
+    impl Session {
+        fn connect(port: u16) -> Session {
+            Session::recorded(port, None)
+        }
+
+        fn recorded(port: u16, recorder: Option<Arc<Recorder>>) -> Session {
+            let (connection, game_info) = connect("127.0.0.1", port, recorder).unwrap();
+            let game_data = Arc::new(Mutex::new(game_info));
+            let (sender, events) = mpsc::channel();
+            connection.spawn_reader(game_data.clone(), sender).unwrap();
+
+            Session {
+                app: App::new(Screen::CreateCharacter(CharacterForm::new())),
+                game_data,
+                connection,
+                events,
+            }
+        }
+
+        /// Connects and gets a character accepted.
+        fn join(port: u16, name: &str) -> Session {
+            let mut session = Session::connect(port);
+            session.send_character(name);
+            session.wait_for(|msg| matches!(*msg, ServerMessage::Accept(CHARACTER_TYPE)));
+            session
+        }
+
+        /// Joins and starts, leaving the player in the start room.
+        fn play(port: u16, name: &str) -> Session {
+            let mut session = Session::join(port, name);
+            session.submit("/start");
+            session.arrive("Entrance");
+            session
+        }
+
+        fn send_character(&mut self, name: &str) {
+            if let Screen::CreateCharacter(ref mut form) = self.app.screen {
+                form.status = FormStatus::Waiting(Instant::now());
+            }
+            self.perform(Action::SendCharacter(character(name)));
+        }
+
+        fn perform(&mut self, action: Action) {
+            perform(action, &mut self.app, &self.game_data, Some(&self.connection));
+        }
+
+        fn submit(&mut self, line: &str) {
+            self.perform(Action::Submit(String::from(line)));
+        }
+
+        /// Handles one event the way the main loop does.
+        fn handle(&mut self, evt: Event) -> Option<ServerMessage> {
+            let msg = match evt {
+                Event::Server(msg) => msg,
+                Event::Disconnected(e) => panic!("Disconnected: {}", e.report()),
+                _ => return None,
+            };
+            let action = {
+                let mut game_info = self.game_data.lock().unwrap();
+                self.app.handle_server_message(&msg, &mut game_info)
+            };
+            if let Some(action) = action {
+                self.perform(action);
+            }
+            Some(msg)
+        }
+
+        fn wait_for<F: Fn(&ServerMessage) -> bool>(&mut self, done: F) -> ServerMessage {
+            loop {
+                let evt = self.events
+                    .recv_timeout(Duration::from_secs(REPLY_TIMEOUT_SECS))
+                    .expect("Timed out waiting on the server.");
+                if let Some(msg) = self.handle(evt) {
+                    if done(&msg) {
+                        return msg;
+                    }
                 }
             }
-            Err(_) => {
-                eprintln!("Failed to handle key input.");
+        }
+
+        /// Waits for the player's own character, which the server sends
+        /// last after anything that changes it.
+        fn wait_for_player(&mut self) {
+            let name = self.game_info().player.name.clone();
+            self.wait_for(|msg| match *msg {
+                ServerMessage::Character(ref entity) => entity.name == name,
+                _ => false,
+            });
+        }
+
+        /// Waits until the player has entered the room called `room` and
+        /// everything about it has arrived.
+        fn arrive(&mut self, room: &str) {
+            self.wait_for(|msg| match *msg {
+                ServerMessage::Room(ref entered) => entered.name == room,
+                _ => false,
+            });
+            self.wait_for_player();
+        }
+
+        fn wait_for_error(&mut self) {
+            self.wait_for(|msg| matches!(*msg, ServerMessage::Error { .. }));
+        }
+
+        fn game_info(&self) -> MutexGuard<'_, GameInformation> {
+            self.game_data.lock().unwrap()
+        }
+
+        fn has_notice(&self, content: &str) -> bool {
+            self.game_info()
+                .messages
+                .iter()
+                .any(|msg| msg.notice && msg.content == content)
+        }
+    }
+
+    #[test]
+    fn scripts_cant_keep_reloading_themselves() {
+        let dir = env::temp_dir().join(format!("lurk-scripts-{}", process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        fs::write(dir.join("again.rhai"), "command(\"/reload\");").unwrap();
+
+        let (notify, _events) = mpsc::channel();
+        let mut client = Client {
+            app: App::new(Screen::Main),
+            game_data: Arc::new(Mutex::new(GameInformation::new(Game::default()))),
+            connection: None,
+            server: None,
+            recorder: None,
+            notify,
+            scripts: Scripts::new(Some(dir.clone())),
+        };
+        client.perform(Action::Submit(String::from("/reload")));
+        client.reload_if_asked();
+        fs::remove_dir_all(&dir).unwrap();
+
+        assert!(!client.app.reload_scripts);
+        let game_info = client.game_data.lock().unwrap();
+        let summaries = game_info
+            .messages
+            .iter()
+            .filter(|msg| msg.notice && msg.content.starts_with("Loaded 1 script(s)"));
+        assert_eq!(summaries.count(), 1);
+    }
+
+    #[test]
+    fn example_world_file_loads() {
+        let world = World::load(concat!(env!("CARGO_MANIFEST_DIR"), "/data/world.toml")).unwrap();
+        assert_eq!(world.rooms.len(), World::example().rooms.len());
+    }
+
+    #[test]
+    fn starting_enters_the_first_room() {
+        let session = Session::play(start_server(), "Alice");
+
+        assert!(matches!(session.app.screen, Screen::Main));
+        let game_info = session.game_info();
+        assert_eq!(game_info.current_room.name, "Entrance");
+        assert_eq!(game_info.adjacent_rooms.len(), 1);
+        assert_eq!(game_info.adjacent_rooms[0].name, "Great Hall");
+        assert!(game_info.messages.iter().any(|msg| msg.sender == "Narrator"));
+    }
+
+    #[test]
+    fn characters_can_be_typed_as_a_command() {
+        let mut session = Session::connect(start_server());
+        session.submit("/character \"Bob the Bold\" 60 20 20 Typed in plain mode.");
+        session.wait_for(|msg| matches!(*msg, ServerMessage::Accept(CHARACTER_TYPE)));
+
+        assert!(matches!(session.app.screen, Screen::Main));
+        assert_eq!(session.game_info().player.name, "Bob the Bold");
+    }
+
+    #[test]
+    fn taken_names_are_rejected() {
+        let port = start_server();
+        let _alice = Session::join(port, "Alice");
+
+        let mut impostor = Session::connect(port);
+        impostor.send_character("Alice");
+        impostor.wait_for_error();
+
+        assert!(matches!(
+            impostor.app.screen,
+            Screen::CreateCharacter(CharacterForm {
+                status: FormStatus::Rejected(_),
+                ..
+            })
+        ));
+    }
+
+    #[test]
+    fn travel_walks_back_along_discovered_rooms() {
+        let mut session = Session::play(start_server(), "Alice");
+        let steps = [("/go 2", "Great Hall"), ("/go Cellar", "Cellar"), ("/go 5", "Vault")];
+        for &(line, room) in &steps {
+            session.submit(line);
+            session.arrive(room);
+        }
+        assert_eq!(session.game_info().current_room.name, "Vault");
+
+        session.submit("/travel Entrance");
+        session.arrive("Entrance");
+
+        assert_eq!(session.game_info().current_room.name, "Entrance");
+        assert!(session.app.travel.is_none());
+        assert!(session.has_notice("Arrived at Entrance."));
+    }
+
+    #[test]
+    fn unknown_exits_are_refused() {
+        let mut session = Session::play(start_server(), "Alice");
+        session.submit("/go 5");
+        session.wait_for_error();
+
+        assert_eq!(session.game_info().current_room.name, "Entrance");
+        let game_info = session.game_info();
+        assert!(game_info.messages.iter().all(|msg| msg.sender != "Server"));
+        let error = game_info.notifications.history.back().unwrap();
+        assert_eq!(error.code, ErrorCode::BadRoom);
+        assert_eq!(error.command, Some(String::from("/go 5")));
+    }
+
+    #[test]
+    fn reconnecting_resumes_play() {
+        let port = start_server();
+        let mut session = Session::play(port, "Alice");
+        session.submit("/go 2");
+        session.arrive("Great Hall");
+
+        // Leaving makes the server hang up on us, which the reader sees the
+        // same way as a dropped connection.
+        session.connection.send(&ClientMessage::Leave).unwrap();
+        loop {
+            let evt = session.events
+                .recv_timeout(Duration::from_secs(REPLY_TIMEOUT_SECS))
+                .expect("Timed out waiting on the server.");
+            if let Event::Disconnected(_) = evt {
+                break;
             }
         }
+
+        let (connection, game) = net::join("127.0.0.1", port, None).unwrap();
+        let (sender, events) = mpsc::channel();
+        resume(&connection, game, &session.app, &session.game_data, sender).unwrap();
+        session.connection = connection;
+        session.events = events;
+        session.arrive("Entrance");
+
+        let game_info = session.game_info();
+        assert_eq!(game_info.player.name, "Alice");
+        assert_eq!(game_info.current_room.name, "Entrance");
+        assert!(game_info.notifications.history.is_empty());
+        let greetings = game_info.messages.iter().filter(|msg| msg.sender == "Narrator");
+        assert_eq!(greetings.count(), 2);
+    }
+
+    #[test]
+    fn replays_show_what_was_recorded() {
+        let path = env::temp_dir().join(format!("lurk-recording-{}.jsonl", process::id()));
+        let path = path.to_str().unwrap().to_string();
+
+        let port = start_server();
+        let _bob = Session::play(port, "Bob");
+        let _carol = Session::play(port, "Carol");
+        let recorder = Arc::new(Recorder::create(&path).unwrap());
+        let mut session = Session::recorded(port, Some(recorder));
+        session.send_character("Alice");
+        session.wait_for(|msg| matches!(*msg, ServerMessage::Accept(CHARACTER_TYPE)));
+        session.submit("/start");
+        session.arrive("Entrance");
+        // One line in the feed, though it's sent to Bob and Carol apart.
+        session.submit("/say Hello, both of you.");
+        session.submit("/go 2");
+        session.arrive("Great Hall");
+        session.submit("/fight");
+        session.wait_for_player();
+
+        let entries = recording::load(&path).unwrap();
+        fs::remove_file(&path).unwrap();
+        let replayed = Arc::new(Mutex::new(GameInformation::new(Game::default())));
+        let (sender, _events) = mpsc::channel();
+        recording::spawn_replay(entries, path, 1000.0, replayed.clone(), sender)
+            .unwrap()
+            .join()
+            .unwrap();
+
+        let live = session.game_info();
+        let replayed = replayed.lock().unwrap();
+        assert_eq!(replayed.game.description, live.game.description);
+        assert_eq!(replayed.current_room.name, "Great Hall");
+        assert_eq!(replayed.player.name, "Alice");
+        assert_eq!(replayed.player.health, live.player.health);
+        assert_eq!(replayed.combat.totals.damage_dealt, live.combat.totals.damage_dealt);
+        assert_eq!(replayed.map.rooms.len(), live.map.rooms.len());
+        let from_server = |game_info: &GameInformation| -> Vec<String> {
+            game_info
+                .messages
+                .iter()
+                .filter(|msg| !msg.notice)
+                .map(|msg| msg.content.clone())
+                .collect()
+        };
+        assert_eq!(from_server(&replayed), from_server(&live));
+        let said = replayed.messages.iter().filter(|msg| msg.content == "Hello, both of you.");
+        assert_eq!(said.count(), 1);
+    }
+
+    #[test]
+    fn fights_are_logged() {
+        let mut session = Session::play(start_server(), "Alice");
+        session.submit("/go 2");
+        session.arrive("Great Hall");
+        session.submit("/fight");
+        session.wait_for_player();
+
+        let game_info = session.game_info();
+        assert_eq!(game_info.combat.totals.fights, 1);
+        assert!(game_info.combat.totals.damage_dealt > 0);
+        assert!(game_info.combat.totals.damage_taken > 0);
+    }
+
+    #[test]
+    fn whispers_reach_the_other_player() {
+        let port = start_server();
+        let mut alice = Session::play(port, "Alice");
+        let mut bob = Session::play(port, "Bob");
+        // The narrator's greeting is addressed to Bob but isn't a whisper.
+        assert_eq!(bob.game_info().last_whisper_from, None);
+
+        alice.submit("/tell Bob Meet me in the cellar.");
+        bob.wait_for(|msg| matches!(*msg, ServerMessage::Message(_)));
+
+        let game_info = bob.game_info();
+        assert_eq!(game_info.last_whisper_from, Some(String::from("Alice")));
+        assert!(game_info
+            .messages
+            .iter()
+            .any(|msg| msg.content == "Meet me in the cellar."));
     }
 }
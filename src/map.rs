@@ -0,0 +1,201 @@
+use std::collections::{BTreeMap, BTreeSet, HashSet, VecDeque};
+
+use game::Room;
+
+pub struct MapRoom {
+    pub room: Room,
+    /// Whether we've been inside, as opposed to only seen it as an exit.
+    pub visited: bool,
+}
+
+/// One row of the map as drawn: a room placed beneath the room it was
+/// reached from.
+pub struct MapLine {
+    pub number: u16,
+    pub depth: usize,
+    /// Set when the room was already listed higher up, the line only shows
+    /// that a second path leads there.
+    pub repeat: bool,
+}
+
+/// Every room and connection seen this session.
+#[derive(Default)]
+pub struct RoomMap {
+    pub rooms: BTreeMap<u16, MapRoom>,
+    edges: BTreeSet<(u16, u16)>,
+}
+
+impl RoomMap {
+    pub fn visit(&mut self, room: &Room) {
+        let entry = self.rooms.entry(room.number).or_insert_with(|| MapRoom {
+            room: room.clone(),
+            visited: true,
+        });
+        entry.room = room.clone();
+        entry.visited = true;
+    }
+
+    pub fn connect(&mut self, from: u16, to: &Room) {
+        self.rooms.entry(to.number).or_insert_with(|| MapRoom {
+            room: to.clone(),
+            visited: false,
+        });
+        self.edges.insert((from, to.number));
+    }
+
+    pub fn exits(&self, from: u16) -> Vec<u16> {
+        self.edges
+            .range((from, 0)..=(from, u16::MAX))
+            .map(|&(_, to)| to)
+            .collect()
+    }
+
+    /// Looks a room up by name, ignoring case.
+    pub fn find(&self, name: &str) -> Option<u16> {
+        self.rooms
+            .values()
+            .find(|r| r.room.name.eq_ignore_ascii_case(name))
+            .map(|r| r.room.number)
+    }
+
+    /// The fewest rooms to walk through to get from `from` to `to`, not
+    /// counting `from`. Only connections seen so far are considered.
+    pub fn path(&self, from: u16, to: u16) -> Option<Vec<u16>> {
+        let mut came_from = BTreeMap::new();
+        let mut queue = VecDeque::new();
+        queue.push_back(from);
+
+        while let Some(number) = queue.pop_front() {
+            if number == to {
+                let mut path = Vec::new();
+                let mut step = to;
+                while step != from {
+                    path.push(step);
+                    step = came_from[&step];
+                }
+                path.reverse();
+                return Some(path);
+            }
+            for next in self.exits(number) {
+                if next != from && !came_from.contains_key(&next) {
+                    came_from.insert(next, number);
+                    queue.push_back(next);
+                }
+            }
+        }
+
+        None
+    }
+
+    /// Lays the map out as a tree, breadth first from `root` so each room
+    /// sits under the closest route to it. Rooms that can't be reached from
+    /// `root` are listed after it.
+    pub fn tree(&self, root: u16) -> Vec<MapLine> {
+        let mut lines = Vec::new();
+        let mut listed = HashSet::new();
+
+        let mut roots = vec![root];
+        roots.extend(self.rooms.keys().cloned().filter(|n| *n != root));
+
+        for start in roots {
+            if listed.contains(&start) || !self.rooms.contains_key(&start) {
+                continue;
+            }
+
+            // Depth first output over a breadth first spanning tree.
+            let mut parents: BTreeMap<u16, Vec<u16>> = BTreeMap::new();
+            let mut seen = HashSet::new();
+            let mut queue = VecDeque::new();
+            seen.insert(start);
+            queue.push_back(start);
+            while let Some(number) = queue.pop_front() {
+                for to in self.exits(number) {
+                    if !listed.contains(&to) && seen.insert(to) {
+                        parents.entry(number).or_default().push(to);
+                        queue.push_back(to);
+                    }
+                }
+            }
+
+            let mut stack = vec![(start, 0, None)];
+            while let Some((number, depth, parent)) = stack.pop() {
+                lines.push(MapLine {
+                    number,
+                    depth,
+                    repeat: false,
+                });
+                listed.insert(number);
+
+                let children = parents.get(&number).cloned().unwrap_or_default();
+                for to in self.exits(number) {
+                    // The way back is implied, so isn't worth a line.
+                    if Some(to) != parent && !children.contains(&to) && listed.contains(&to) {
+                        lines.push(MapLine {
+                            number: to,
+                            depth: depth + 1,
+                            repeat: true,
+                        });
+                    }
+                }
+                for child in children.into_iter().rev() {
+                    stack.push((child, depth + 1, Some(number)));
+                }
+            }
+        }
+
+        lines
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use game::Room;
+    use super::RoomMap;
+
+    fn room(number: u16) -> Room {
+        Room {
+            name: format!("Room {}", number),
+            description: String::new(),
+            number,
+        }
+    }
+
+    /// 1 <-> 2 -> 3, with 4 only leading out to 1.
+    fn map() -> RoomMap {
+        let mut map = RoomMap::default();
+        map.visit(&room(1));
+        map.connect(1, &room(2));
+        map.connect(2, &room(1));
+        map.connect(2, &room(3));
+        map.visit(&room(4));
+        map.connect(4, &room(1));
+        map
+    }
+
+    #[test]
+    fn path_to_a_neighbour_is_one_step() {
+        assert_eq!(map().path(1, 2), Some(vec![2]));
+        assert_eq!(map().path(1, 3), Some(vec![2, 3]));
+        assert_eq!(map().path(1, 1), Some(vec![]));
+    }
+
+    #[test]
+    fn path_to_an_unreachable_room_is_none() {
+        assert_eq!(map().path(1, 4), None);
+        assert_eq!(map().path(3, 1), None);
+        assert_eq!(map().path(1, 99), None);
+    }
+
+    #[test]
+    fn unreachable_rooms_are_listed_after_the_root() {
+        let lines: Vec<_> = map()
+            .tree(1)
+            .iter()
+            .map(|line| (line.number, line.depth, line.repeat))
+            .collect();
+        assert_eq!(
+            lines,
+            vec![(1, 0, false), (2, 1, false), (3, 2, false), (4, 0, false), (1, 1, true)]
+        );
+    }
+}
@@ -0,0 +1,229 @@
+use std::net::{Shutdown, TcpStream};
+use std::sync::mpsc::Sender;
+use std::sync::{Arc, Mutex};
+use std::thread::{self, JoinHandle};
+use std::time::Duration;
+
+use chrono::prelude::*;
+use chrono;
+
+use error::{Error, Result};
+use events::Event;
+use game::{Game, GameInformation, Message};
+use protocol::{self, ClientMessage, ServerMessage};
+use recording::{Packet, Recorder};
+
+// The wait between reconnection attempts doubles from the first to the last.
+const FIRST_RETRY_SECS: u64 = 1;
+const MAX_RETRY_SECS: u64 = 60;
+
+/// How the link to the server is doing, as shown in the top bar.
+#[derive(Clone, PartialEq)]
+pub enum Status {
+    /// No server was given, the mock data is showing.
+    Offline,
+    /// Connected to the server at this address.
+    Connected(String),
+    /// The connection dropped. Attempt number `attempt` is made at
+    /// `retry_at`.
+    Reconnecting {
+        attempt: u32,
+        retry_at: DateTime<Local>,
+    },
+    /// Playing back a recording instead of talking to a server.
+    Replaying {
+        path: String,
+        speed: f64,
+        finished: bool,
+    },
+}
+
+impl Status {
+    /// The status as a line of text, with any countdown worked out at `now`.
+    pub fn describe(&self, now: DateTime<Local>) -> String {
+        match *self {
+            Status::Offline => String::from("Offline, showing example data"),
+            Status::Connected(ref address) => format!("Connected to {}", address),
+            Status::Reconnecting { attempt, retry_at } => {
+                let wait = (retry_at - now).num_seconds();
+                if wait > 0 {
+                    format!("Disconnected, retrying in {}s (attempt {})", wait, attempt)
+                } else {
+                    format!("Disconnected, reconnecting (attempt {})", attempt)
+                }
+            }
+            Status::Replaying {
+                ref path,
+                finished: true,
+                ..
+            } => format!("Replay of {} finished", path),
+            Status::Replaying {
+                ref path, speed, ..
+            } => format!("Replaying {} at {}x", path, speed),
+        }
+    }
+}
+
+/// How long to wait before reconnection attempt `attempt`, counting from 1.
+pub fn backoff(attempt: u32) -> Duration {
+    let doublings = attempt.saturating_sub(1).min(16);
+    Duration::from_secs((FIRST_RETRY_SECS << doublings).min(MAX_RETRY_SECS))
+}
+
+/// Connects and waits for the server's introduction. Everything from the
+/// introduction on goes to `recorder`, if given.
+pub fn join(host: &str, port: u16, recorder: Option<Arc<Recorder>>) -> Result<(Connection, Game)> {
+    let mut connection = Connection::connect(host, port)?;
+    connection.recorder = recorder;
+    let game = connection.handshake()?;
+    Ok((connection, game))
+}
+
+/// Spawns a thread that tries to connect to the server again, waiting longer
+/// after every failure, until it gets through or nobody is listening on
+/// `notify` any more. Each wait is announced with `Event::Status`, failures
+/// are noted in the feed and the new connection arrives as
+/// `Event::Reconnected`.
+pub fn spawn_reconnect(
+    host: String,
+    port: u16,
+    recorder: Option<Arc<Recorder>>,
+    game_data: Arc<Mutex<GameInformation>>,
+    notify: Sender<Event>,
+) -> Result<JoinHandle<()>> {
+    thread::Builder::new()
+        .name(String::from("lurk-reconnect"))
+        .spawn(move || {
+            let mut attempt = 1;
+            loop {
+                let delay = backoff(attempt);
+                let retry_at = Local::now() + chrono::Duration::seconds(delay.as_secs() as i64);
+                if notify.send(Event::Status(Status::Reconnecting { attempt, retry_at })).is_err() {
+                    return;
+                }
+                thread::sleep(delay);
+
+                match join(&host, port, recorder.clone()) {
+                    Ok((connection, game)) => {
+                        let _ = notify.send(Event::Reconnected(connection, game));
+                        return;
+                    }
+                    Err(e) => {
+                        let mut game_info = game_data.lock().expect("Failed to lock game data.");
+                        game_info.notice(format!("Reconnecting failed. ({})", e.report()));
+                    }
+                }
+                attempt += 1;
+            }
+        })
+        .map_err(Error::network("Failed to spawn reconnection thread"))
+}
+
+pub struct Connection {
+    stream: TcpStream,
+    recorder: Option<Arc<Recorder>>,
+}
+
+impl Connection {
+    pub fn connect(host: &str, port: u16) -> Result<Connection> {
+        let stream = TcpStream::connect((host, port))
+            .map_err(Error::network(format!("Failed to connect to {}:{}", host, port)))?;
+        stream
+            .set_nodelay(true)
+            .map_err(Error::network("Failed to configure connection"))?;
+
+        Ok(Connection {
+            stream,
+            recorder: None,
+        })
+    }
+
+    fn record(&self, packet: Packet) {
+        if let Some(ref recorder) = self.recorder {
+            recorder.record(packet);
+        }
+    }
+
+    /// Reads packets until the server has introduced its game. Servers send
+    /// VERSION (if they support it) followed by GAME right after accepting.
+    pub fn handshake(&mut self) -> Result<Game> {
+        loop {
+            let msg = protocol::read_server_message(&mut self.stream)?;
+            self.record(Packet::In(msg.clone()));
+            match msg {
+                ServerMessage::Game(game) => return Ok(game),
+                ServerMessage::Version { .. } => {}
+                ServerMessage::Error { message, .. } => {
+                    return Err(Error::Protocol(format!("Server refused connection: {}", message)))
+                }
+                _ => {
+                    return Err(Error::Protocol(String::from(
+                        "Server did not send a game description.",
+                    )))
+                }
+            }
+        }
+    }
+
+    /// Another handle on the same connection.
+    pub fn try_clone(&self) -> Result<Connection> {
+        let stream = self.stream
+            .try_clone()
+            .map_err(Error::network("Failed to clone connection"))?;
+        Ok(Connection {
+            stream,
+            recorder: self.recorder.clone(),
+        })
+    }
+
+    /// Adds our copy of a chat message to the recording, so a replay shows
+    /// it the way it was shown.
+    pub fn record_echo(&self, msg: &Message) {
+        self.record(Packet::Echo(msg.clone()));
+    }
+
+    pub fn send(&self, msg: &ClientMessage) -> Result<()> {
+        protocol::write_client_message(&mut &self.stream, msg)?;
+        self.record(Packet::Out(msg.clone()));
+        Ok(())
+    }
+
+    /// Spawns the thread that owns the receiving half of the connection and
+    /// applies every packet to the shared game state as it arrives. Each
+    /// packet is then passed on to `notify` so the UI can redraw and anything
+    /// waiting on a reply can see it.
+    pub fn spawn_reader(
+        &self,
+        game_data: Arc<Mutex<GameInformation>>,
+        notify: Sender<Event>,
+    ) -> Result<JoinHandle<()>> {
+        let mut stream = self.stream
+            .try_clone()
+            .map_err(Error::network("Failed to clone connection for reading"))?;
+        let recorder = self.recorder.clone();
+
+        thread::Builder::new()
+            .name(String::from("lurk-reader"))
+            .spawn(move || loop {
+                let msg = protocol::read_server_message(&mut stream);
+                let mut game_info = game_data.lock().expect("Failed to lock game data.");
+                match msg {
+                    Ok(msg) => {
+                        if let Some(ref recorder) = recorder {
+                            recorder.record(Packet::In(msg.clone()));
+                        }
+                        game_info.apply(msg.clone());
+                        let _ = notify.send(Event::Server(msg));
+                    }
+                    Err(e) => {
+                        // Whatever is left of the socket goes too, so
+                        // nothing more is sent on a connection that's gone.
+                        let _ = stream.shutdown(Shutdown::Both);
+                        let _ = notify.send(Event::Disconnected(e));
+                        break;
+                    }
+                }
+            })
+            .map_err(Error::network("Failed to spawn network reader"))
+    }
+}
@@ -0,0 +1,185 @@
+use std::collections::VecDeque;
+
+use chrono::prelude::*;
+use chrono::Duration;
+
+const MAX_HISTORY: usize = 100;
+
+// How long a notification stays up over the main screen.
+const SHOW_SECS: i64 = 8;
+
+// ERROR doesn't say what it's answering, so it's put down to the last command
+// sent if that was recent enough.
+const REPLY_WINDOW_SECS: i64 = 5;
+
+/// The reason given in an ERROR packet.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum ErrorCode {
+    Other,
+    BadRoom,
+    PlayerExists,
+    BadMonster,
+    StatError,
+    NotReady,
+    NoTarget,
+    NoFight,
+    NoPvp,
+}
+
+impl ErrorCode {
+    /// Codes this client doesn't know are treated as `Other`.
+    pub fn from_code(code: u8) -> ErrorCode {
+        match code {
+            1 => ErrorCode::BadRoom,
+            2 => ErrorCode::PlayerExists,
+            3 => ErrorCode::BadMonster,
+            4 => ErrorCode::StatError,
+            5 => ErrorCode::NotReady,
+            6 => ErrorCode::NoTarget,
+            7 => ErrorCode::NoFight,
+            8 => ErrorCode::NoPvp,
+            _ => ErrorCode::Other,
+        }
+    }
+
+    pub fn title(self) -> &'static str {
+        match self {
+            ErrorCode::Other => "Error",
+            ErrorCode::BadRoom => "Bad room",
+            ErrorCode::PlayerExists => "Player exists",
+            ErrorCode::BadMonster => "Bad monster",
+            ErrorCode::StatError => "Stat error",
+            ErrorCode::NotReady => "Not ready",
+            ErrorCode::NoTarget => "No target",
+            ErrorCode::NoFight => "No fight",
+            ErrorCode::NoPvp => "No PvP",
+        }
+    }
+
+    pub fn explanation(self) -> &'static str {
+        match self {
+            ErrorCode::Other => "The server turned that down.",
+            ErrorCode::BadRoom => "That room doesn't exist or can't be reached from here.",
+            ErrorCode::PlayerExists => "Somebody is already playing under that name.",
+            ErrorCode::BadMonster => "There's no monster by that name here.",
+            ErrorCode::StatError => "Those stats break the game's rules.",
+            ErrorCode::NotReady => "You can't do that yet.",
+            ErrorCode::NoTarget => "Whoever that was meant for isn't here.",
+            ErrorCode::NoFight => "There's nothing here to fight.",
+            ErrorCode::NoPvp => "This server doesn't allow players to fight each other.",
+        }
+    }
+
+    pub fn suggestion(self) -> &'static str {
+        match self {
+            ErrorCode::Other => "See the server's message for why.",
+            ErrorCode::BadRoom => "Pick one of the exits listed for this room.",
+            ErrorCode::PlayerExists => "Choose a different name.",
+            ErrorCode::BadMonster => "Check the name against the room's occupants.",
+            ErrorCode::StatError => "Keep attack, defense and regen within the points given.",
+            ErrorCode::NotReady => "Make a character and /start first.",
+            ErrorCode::NoTarget => "Check the name, they may have left.",
+            ErrorCode::NoFight => "Find a room with a living monster in it.",
+            ErrorCode::NoPvp => "Fight monsters with /fight instead.",
+        }
+    }
+}
+
+/// An ERROR from the server.
+#[derive(Clone)]
+pub struct Notification {
+    pub code: ErrorCode,
+    /// What the server said.
+    pub message: String,
+    /// The command it was most likely a reply to.
+    pub command: Option<String>,
+    pub received: DateTime<Local>,
+}
+
+impl Notification {
+    pub fn summary(&self) -> String {
+        let mut summary = format!("{}: {}", self.code.title(), self.message);
+        if let Some(ref command) = self.command {
+            summary.push_str(&format!(" (after {})", command));
+        }
+        summary
+    }
+}
+
+/// Every ERROR received this session, oldest first.
+#[derive(Clone, Default)]
+pub struct Notifications {
+    pub history: VecDeque<Notification>,
+    /// The last command, the type of its last packet and when it went.
+    last_sent: Option<(String, u8, DateTime<Local>)>,
+}
+
+impl Notifications {
+    /// Called whenever a command goes out, so an ERROR that follows can be
+    /// tied back to it. `packet_type` is the type of the last packet sent
+    /// for it.
+    pub fn sent(&mut self, command: String, packet_type: u8) {
+        self.last_sent = Some((command, packet_type, Local::now()));
+    }
+
+    /// The server took a packet of `packet_type`. If that was the last
+    /// command's, whatever comes next isn't about it.
+    pub fn accepted(&mut self, packet_type: u8) {
+        let taken = match self.last_sent {
+            Some((_, sent_type, _)) => sent_type == packet_type,
+            None => false,
+        };
+        if taken {
+            self.last_sent = None;
+        }
+    }
+
+    pub fn push(&mut self, code: u8, message: String) {
+        let received = Local::now();
+        let command = match self.last_sent.take() {
+            Some((command, _, sent)) if received - sent <= Duration::seconds(REPLY_WINDOW_SECS) => {
+                Some(command)
+            }
+            _ => None,
+        };
+
+        self.history.push_back(Notification {
+            code: ErrorCode::from_code(code),
+            message,
+            command,
+            received,
+        });
+        while self.history.len() > MAX_HISTORY {
+            self.history.pop_front();
+        }
+    }
+
+    /// The notification to show at `now`, if one arrived recently.
+    pub fn showing(&self, now: DateTime<Local>) -> Option<&Notification> {
+        self.history.back().filter(|latest| {
+            let age = now - latest.received;
+            age >= Duration::zero() && age < Duration::seconds(SHOW_SECS)
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use protocol::{CHARACTER_TYPE, MESSAGE_TYPE};
+    use super::Notifications;
+
+    #[test]
+    fn errors_are_tied_to_the_command_until_it_is_accepted() {
+        let mut notifications = Notifications::default();
+        notifications.sent(String::from("creating Alice"), CHARACTER_TYPE);
+        // Someone else's business.
+        notifications.accepted(MESSAGE_TYPE);
+        notifications.push(2, String::from("Taken."));
+        assert_eq!(notifications.history[0].command, Some(String::from("creating Alice")));
+
+        notifications.sent(String::from("creating Bob"), CHARACTER_TYPE);
+        notifications.accepted(CHARACTER_TYPE);
+        notifications.push(0, String::from("Something else."));
+        assert_eq!(notifications.history[1].command, None);
+    }
+}
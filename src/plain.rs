@@ -0,0 +1,134 @@
+use std::io::{self, Write};
+
+use chrono::prelude::*;
+
+use app::{Action, App, Screen};
+use game::{Entity, GameInformation, Message};
+use net::Status;
+use protocol::ServerMessage;
+
+/// What line mode writes to stdout and how it reads stdin. Every call
+/// comes after the change it reports has been applied to `game_info`.
+pub trait Output {
+    /// Called once before any events.
+    fn introduce(&mut self, app: &App, game_info: &GameInformation) -> io::Result<()>;
+
+    /// Reports the messages that have reached the feed since the last call.
+    fn messages(&mut self, game_info: &GameInformation) -> io::Result<()>;
+
+    /// Reports what a packet changed that doesn't show up in the feed.
+    fn packet(&mut self, msg: &ServerMessage, game_info: &GameInformation) -> io::Result<()>;
+
+    fn status(&mut self, status: &Status) -> io::Result<()>;
+
+    /// What to do about a line read from stdin, if anything.
+    fn read(&self, line: String) -> Option<Action>;
+}
+
+/// Writes what happens as plain lines of text, and takes the same commands
+/// as the input section.
+#[derive(Default)]
+pub struct Printer {
+    /// How many of the feed's messages have been printed.
+    printed: usize,
+}
+
+impl Printer {
+    fn print(&self, text: &str) -> io::Result<()> {
+        writeln!(io::stdout(), "{}", text)
+    }
+}
+
+impl Output for Printer {
+    fn introduce(&mut self, app: &App, game_info: &GameInformation) -> io::Result<()> {
+        self.print(&app.connection_status.describe(Local::now()))?;
+        self.print(&game_info.game.description)?;
+        if let Screen::CreateCharacter(_) = app.screen {
+            self.print(&format!(
+                "Make a character with /character <name> <attack> <defense> <regen> \
+                 [description]. You have {} points to spend.",
+                game_info.game.init_points
+            ))?;
+        }
+        Ok(())
+    }
+
+    fn messages(&mut self, game_info: &GameInformation) -> io::Result<()> {
+        let stdout = io::stdout();
+        let mut stdout = stdout.lock();
+        for msg in game_info.messages_since(self.printed) {
+            writeln!(stdout, "{}", message_line(msg, game_info))?;
+        }
+        self.printed = game_info.message_total;
+        Ok(())
+    }
+
+    fn packet(&mut self, msg: &ServerMessage, game_info: &GameInformation) -> io::Result<()> {
+        match *msg {
+            ServerMessage::Game(ref game) => self.print(&game.description),
+            ServerMessage::Room(ref room) => {
+                self.print(&format!("-- {} (#{}) --", room.name, room.number))?;
+                self.print(&room.description)
+            }
+            ServerMessage::Connection(ref room) => {
+                self.print(&format!("Exit: {} (#{})", room.name, room.number))
+            }
+            ServerMessage::Character(ref entity) => {
+                let is_player = entity.name == game_info.player.name;
+                if is_player || entity.location == game_info.current_room.number {
+                    self.print(&entity_line(entity, is_player))
+                } else {
+                    Ok(())
+                }
+            }
+            ServerMessage::Error { .. } => match game_info.notifications.history.back() {
+                Some(error) => self.print(&format!("{}: {}", error.code.title(), error.message)),
+                None => Ok(()),
+            },
+            ServerMessage::Message(_)
+            | ServerMessage::Accept(_)
+            | ServerMessage::Version { .. } => Ok(()),
+        }
+    }
+
+    fn status(&mut self, status: &Status) -> io::Result<()> {
+        self.print(&status.describe(Local::now()))
+    }
+
+    fn read(&self, line: String) -> Option<Action> {
+        if line.trim().is_empty() {
+            None
+        } else {
+            Some(Action::Submit(line))
+        }
+    }
+}
+
+fn message_line(msg: &Message, game_info: &GameInformation) -> String {
+    let player_name = &game_info.player.name;
+    let time = msg.received.format("%H:%M:%S");
+
+    if game_info.is_whisper(msg) {
+        format!("[{}] {} whispers: {}", time, msg.sender, msg.content)
+    } else if !player_name.is_empty() && msg.sender == *player_name && !msg.receiver.is_empty() {
+        format!("[{}] You -> {}: {}", time, msg.receiver, msg.content)
+    } else {
+        format!("[{}] {}: {}", time, msg.sender, msg.content)
+    }
+}
+
+fn entity_line(entity: &Entity, is_player: bool) -> String {
+    let name = if is_player {
+        format!("You ({})", entity.name)
+    } else if entity.is_monster {
+        format!("{} (monster)", entity.name)
+    } else {
+        entity.name.clone()
+    };
+    let state = if entity.is_alive { "" } else { ", dead" };
+
+    format!(
+        "{}: {} health, {} attack, {} defense, {} regen, {} gold{}",
+        name, entity.health, entity.attack, entity.defense, entity.regen, entity.gold, state
+    )
+}
@@ -0,0 +1,227 @@
+use std::io::{Read, Write};
+use std::net::TcpStream;
+
+use chrono::prelude::*;
+use liblurk::protocol::protocol_message::{Accept, ChangeRoom, Character, Connection, Error, Fight,
+                                          Game, Leave, Loot, LurkMessageBlobify, LurkMessageParse,
+                                          Message, PvpFight, Room, Start};
+
+use error::{Error as ClientError, Result};
+use game;
+
+pub const MESSAGE_TYPE: u8 = 1;
+pub const CHANGE_ROOM_TYPE: u8 = 2;
+pub const FIGHT_TYPE: u8 = 3;
+pub const PVP_FIGHT_TYPE: u8 = 4;
+pub const LOOT_TYPE: u8 = 5;
+pub const START_TYPE: u8 = 6;
+pub const ERROR_TYPE: u8 = 7;
+pub const ACCEPT_TYPE: u8 = 8;
+pub const ROOM_TYPE: u8 = 9;
+pub const CHARACTER_TYPE: u8 = 10;
+pub const GAME_TYPE: u8 = 11;
+pub const LEAVE_TYPE: u8 = 12;
+pub const CONNECTION_TYPE: u8 = 13;
+pub const VERSION_TYPE: u8 = 14;
+
+/// A decoded packet sent to us by the server, already converted into the
+/// client's own game types.
+#[derive(Clone, Serialize, Deserialize)]
+pub enum ServerMessage {
+    Message(game::Message),
+    Error { code: u8, message: String },
+    Accept(u8),
+    Room(game::Room),
+    Character(game::Entity),
+    Game(game::Game),
+    Connection(game::Room),
+    Version { major: u8, minor: u8 },
+}
+
+/// A packet the client sends to the server.
+#[derive(Clone, Serialize, Deserialize)]
+pub enum ClientMessage {
+    Message {
+        receiver: String,
+        sender: String,
+        content: String,
+    },
+    ChangeRoom(u16),
+    Fight,
+    PvpFight(String),
+    Loot(String),
+    Start,
+    Character(game::Entity),
+    Leave,
+}
+
+impl ClientMessage {
+    /// The type byte the packet goes out with, which is what an ACCEPT
+    /// names.
+    pub fn type_id(&self) -> u8 {
+        match *self {
+            ClientMessage::Message { .. } => MESSAGE_TYPE,
+            ClientMessage::ChangeRoom(_) => CHANGE_ROOM_TYPE,
+            ClientMessage::Fight => FIGHT_TYPE,
+            ClientMessage::PvpFight(_) => PVP_FIGHT_TYPE,
+            ClientMessage::Loot(_) => LOOT_TYPE,
+            ClientMessage::Start => START_TYPE,
+            ClientMessage::Character(_) => CHARACTER_TYPE,
+            ClientMessage::Leave => LEAVE_TYPE,
+        }
+    }
+}
+
+fn read_type(stream: &mut TcpStream) -> Result<u8> {
+    let mut buf = [0u8; 1];
+    stream
+        .read_exact(&mut buf)
+        .map_err(ClientError::network("Failed to read packet type"))?;
+    Ok(buf[0])
+}
+
+// liblurk predates the VERSION packet, so it is read by hand. Extensions are
+// skipped since the client doesn't make use of any.
+fn read_version(stream: &mut TcpStream) -> Result<ServerMessage> {
+    let mut header = [0u8; 4];
+    stream
+        .read_exact(&mut header)
+        .map_err(ClientError::network("Failed to read version packet"))?;
+
+    let extensions_len = u16::from(header[2]) | (u16::from(header[3]) << 8);
+    let mut extensions = vec![0u8; extensions_len as usize];
+    stream
+        .read_exact(&mut extensions)
+        .map_err(ClientError::network("Failed to read version extensions"))?;
+
+    Ok(ServerMessage::Version {
+        major: header[0],
+        minor: header[1],
+    })
+}
+
+// liblurk doesn't say why a packet couldn't be parsed, so there's no source
+// to keep.
+fn parse<T: LurkMessageParse<T>>(stream: &mut TcpStream, name: &str) -> Result<T> {
+    T::parse_lurk_message_from_stream(stream)
+        .map_err(|_| ClientError::Protocol(format!("Failed to parse {} packet.", name)))
+}
+
+/// Blocks until a full packet has been read from `stream`.
+pub fn read_server_message(stream: &mut TcpStream) -> Result<ServerMessage> {
+    match read_type(stream)? {
+        MESSAGE_TYPE => {
+            let msg: Message = parse(stream, "MESSAGE")?;
+            Ok(ServerMessage::Message(game::Message {
+                sender: msg.sender,
+                receiver: msg.receiver,
+                content: msg.message,
+                received: Local::now(),
+                notice: false,
+            }))
+        }
+        ERROR_TYPE => {
+            let err: Error = parse(stream, "ERROR")?;
+            Ok(ServerMessage::Error {
+                code: err.error_code,
+                message: err.message,
+            })
+        }
+        ACCEPT_TYPE => {
+            let accept: Accept = parse(stream, "ACCEPT")?;
+            Ok(ServerMessage::Accept(accept.accept_type))
+        }
+        ROOM_TYPE => {
+            let room: Room = parse(stream, "ROOM")?;
+            Ok(ServerMessage::Room(game::Room {
+                name: room.room_name,
+                description: room.description,
+                number: room.room_number,
+            }))
+        }
+        CHARACTER_TYPE => {
+            let character: Character = parse(stream, "CHARACTER")?;
+            Ok(ServerMessage::Character(game::Entity {
+                name: character.player_name,
+                is_alive: character.is_alive,
+                join_battle: character.join_battle,
+                is_monster: character.is_monster,
+                is_started: character.is_started,
+                is_ready: character.is_ready,
+                attack: character.attack,
+                defense: character.defense,
+                regen: character.regen,
+                health: character.health,
+                gold: character.gold,
+                location: character.current_room,
+                description: character.description,
+            }))
+        }
+        GAME_TYPE => {
+            let game: Game = parse(stream, "GAME")?;
+            Ok(ServerMessage::Game(game::Game {
+                stat_limit: game.stat_limit,
+                init_points: game.initial_points,
+                description: game.game_description,
+            }))
+        }
+        CONNECTION_TYPE => {
+            let connection: Connection = parse(stream, "CONNECTION")?;
+            Ok(ServerMessage::Connection(game::Room {
+                name: connection.room_name,
+                description: connection.description,
+                number: connection.room_number,
+            }))
+        }
+        VERSION_TYPE => read_version(stream),
+        other => Err(ClientError::Protocol(format!(
+            "Server sent unexpected packet type {}.",
+            other
+        ))),
+    }
+}
+
+pub fn write_client_message<W: Write>(stream: &mut W, msg: &ClientMessage) -> Result<()> {
+    let blob = match *msg {
+        ClientMessage::Message {
+            ref receiver,
+            ref sender,
+            ref content,
+        } => Message {
+            receiver: receiver.clone(),
+            sender: sender.clone(),
+            message: content.clone(),
+        }.produce_lurk_message_blob(),
+        ClientMessage::ChangeRoom(room_number) => {
+            ChangeRoom { room_number }.produce_lurk_message_blob()
+        }
+        ClientMessage::Fight => Fight.produce_lurk_message_blob(),
+        ClientMessage::PvpFight(ref target) => PvpFight {
+            target: target.clone(),
+        }.produce_lurk_message_blob(),
+        ClientMessage::Loot(ref target) => Loot {
+            target: target.clone(),
+        }.produce_lurk_message_blob(),
+        ClientMessage::Start => Start.produce_lurk_message_blob(),
+        ClientMessage::Character(ref entity) => Character {
+            player_name: entity.name.clone(),
+            is_alive: entity.is_alive,
+            join_battle: entity.join_battle,
+            is_monster: entity.is_monster,
+            is_started: entity.is_started,
+            is_ready: entity.is_ready,
+            attack: entity.attack,
+            defense: entity.defense,
+            regen: entity.regen,
+            health: entity.health,
+            gold: entity.gold,
+            current_room: entity.location,
+            description: entity.description.clone(),
+        }.produce_lurk_message_blob(),
+        ClientMessage::Leave => Leave.produce_lurk_message_blob(),
+    };
+
+    stream
+        .write_all(&blob)
+        .map_err(ClientError::network("Failed to send packet"))
+}
@@ -0,0 +1,166 @@
+use std::fs::File;
+use std::io::{BufRead, BufReader, Write};
+use std::sync::mpsc::Sender;
+use std::sync::{Arc, Mutex};
+use std::thread::{self, JoinHandle};
+use std::time::Duration;
+
+use chrono::prelude::*;
+use serde_json;
+
+use error::{Error, Result};
+use events::Event;
+use game::{GameInformation, Message};
+use net::Status;
+use protocol::{ClientMessage, ServerMessage};
+
+/// A packet as it crossed the wire.
+#[derive(Clone, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Packet {
+    In(ServerMessage),
+    Out(ClientMessage),
+    /// Our own copy of a chat message we sent, which never crosses the wire
+    /// but can't be worked out from the packets that carried it.
+    Echo(Message),
+}
+
+/// One line of a recording.
+#[derive(Clone, Serialize, Deserialize)]
+pub struct Entry {
+    pub at: DateTime<Local>,
+    pub packet: Packet,
+}
+
+struct Output {
+    file: Option<File>,
+    error: Option<Error>,
+}
+
+/// Writes every packet sent or received to a file, one JSON object per line.
+/// Shared by the connection's reading and sending halves.
+pub struct Recorder {
+    output: Mutex<Output>,
+}
+
+impl Recorder {
+    pub fn create(path: &str) -> Result<Recorder> {
+        let file = File::create(path).map_err(Error::recording(format!("Failed to create {}", path)))?;
+        Ok(Recorder {
+            output: Mutex::new(Output {
+                file: Some(file),
+                error: None,
+            }),
+        })
+    }
+
+    /// Adds `packet` to the recording. Recording stops at the first failure,
+    /// which is kept for `take_error` rather than getting in the way of play.
+    pub fn record(&self, packet: Packet) {
+        let entry = Entry {
+            at: Local::now(),
+            packet,
+        };
+        let mut output = match self.output.lock() {
+            Ok(output) => output,
+            Err(_) => return,
+        };
+
+        let written = match output.file {
+            Some(ref mut file) => serde_json::to_string(&entry)
+                .map_err(Error::recording("Failed to encode packet for the recording"))
+                .and_then(|line| {
+                    writeln!(file, "{}", line)
+                        .map_err(Error::recording("Failed to write to the recording"))
+                }),
+            None => return,
+        };
+        if let Err(e) = written {
+            output.file = None;
+            output.error = Some(e);
+        }
+    }
+
+    /// Why recording stopped, the first time this is asked after it did.
+    pub fn take_error(&self) -> Option<Error> {
+        self.output.lock().ok().and_then(|mut output| output.error.take())
+    }
+}
+
+/// Reads back a recording made with `--record`.
+pub fn load(path: &str) -> Result<Vec<Entry>> {
+    let file = File::open(path).map_err(Error::recording(format!("Failed to open {}", path)))?;
+
+    let mut entries = Vec::new();
+    for (index, line) in BufReader::new(file).lines().enumerate() {
+        let line = line.map_err(Error::recording(format!("Failed to read {}", path)))?;
+        if line.trim().is_empty() {
+            continue;
+        }
+        let entry = serde_json::from_str(&line).map_err(Error::recording(format!(
+            "Line {} of {} is not a recorded packet",
+            index + 1,
+            path
+        )))?;
+        entries.push(entry);
+    }
+    Ok(entries)
+}
+
+/// Applies a packet we sent the way sending it did at the time.
+fn replay_sent(msg: ClientMessage, game_info: &mut GameInformation) {
+    match msg {
+        ClientMessage::Character(entity) => game_info.player = entity,
+        ClientMessage::Fight | ClientMessage::PvpFight(_) => game_info.combat.begin_fight(),
+        _ => {}
+    }
+}
+
+/// Spawns the thread that plays `entries` back into `game_data` in place of
+/// a connection, `speed` times as fast as they were recorded. Received
+/// packets are passed on to `notify` just as the network reader does.
+pub fn spawn_replay(
+    entries: Vec<Entry>,
+    path: String,
+    speed: f64,
+    game_data: Arc<Mutex<GameInformation>>,
+    notify: Sender<Event>,
+) -> Result<JoinHandle<()>> {
+    thread::Builder::new()
+        .name(String::from("lurk-replay"))
+        .spawn(move || {
+            let mut last = entries.first().map(|entry| entry.at);
+            for entry in entries {
+                if let Some(last) = last {
+                    let gap = (entry.at - last).to_std().unwrap_or_else(|_| Duration::from_secs(0));
+                    let gap = gap.as_secs() as f64 + f64::from(gap.subsec_nanos()) / 1e9;
+                    let wait = gap / speed;
+                    thread::sleep(Duration::new(wait as u64, (wait.fract() * 1e9) as u32));
+                }
+                last = Some(entry.at);
+
+                let mut game_info = game_data.lock().expect("Failed to lock game data.");
+                match entry.packet {
+                    Packet::In(msg) => {
+                        game_info.apply(msg.clone());
+                        if notify.send(Event::Server(msg)).is_err() {
+                            return;
+                        }
+                    }
+                    Packet::Out(msg) => replay_sent(msg, &mut game_info),
+                    Packet::Echo(msg) => game_info.push_message(msg),
+                }
+            }
+
+            game_data
+                .lock()
+                .expect("Failed to lock game data.")
+                .notice(String::from("End of the recording."));
+            let _ = notify.send(Event::Status(Status::Replaying {
+                path,
+                speed,
+                finished: true,
+            }));
+        })
+        .map_err(Error::recording("Failed to spawn replay"))
+}
@@ -0,0 +1,121 @@
+use std::io::{self, Write};
+use std::mem;
+use std::panic;
+use std::process;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Mutex;
+use std::thread;
+
+use libc;
+use signal_hook::consts::{SIGINT, SIGTERM};
+use signal_hook::iterator::Signals;
+use termion;
+
+use error::{Error, Result};
+use net::Connection;
+use protocol::ClientMessage;
+
+// Turns off everything the interface switches on: mouse reporting (as
+// enabled by termion's MouseTerminal) and bracketed paste.
+const RESET_MODES: &str = "\x1b[?1006l\x1b[?1015l\x1b[?1002l\x1b[?1000l\x1b[?2004l";
+
+static ORIGINAL_TERMIOS: Mutex<Option<libc::termios>> = Mutex::new(None);
+static CONNECTION: Mutex<Option<Connection>> = Mutex::new(None);
+// Line mode leaves the terminal alone, so there's nothing to put back.
+static TERMINAL_TAKEN: AtomicBool = AtomicBool::new(false);
+
+/// Remembers the terminal settings from before raw mode was entered. Must
+/// be called before the interface is created.
+pub fn save_terminal_state() {
+    TERMINAL_TAKEN.store(true, Ordering::SeqCst);
+    unsafe {
+        let mut termios: libc::termios = mem::zeroed();
+        if libc::tcgetattr(libc::STDIN_FILENO, &mut termios) == 0 {
+            if let Ok(mut original) = ORIGINAL_TERMIOS.lock() {
+                *original = Some(termios);
+            }
+        }
+    }
+}
+
+/// Registers the connection a LEAVE should be sent on if we go down hard.
+pub fn register_connection(connection: Connection) {
+    if let Ok(mut registered) = CONNECTION.lock() {
+        *registered = Some(connection);
+    }
+}
+
+/// Forgets the registered connection once it has dropped, until the next
+/// one is registered.
+pub fn forget_connection() {
+    if let Ok(mut registered) = CONNECTION.lock() {
+        *registered = None;
+    }
+}
+
+fn leave_server() {
+    // try_lock, as we may be panicking while a lock is held on this thread.
+    if let Ok(mut registered) = CONNECTION.try_lock() {
+        if let Some(connection) = registered.take() {
+            let _ = connection.send(&ClientMessage::Leave);
+        }
+    }
+}
+
+/// Puts the terminal back the way we found it.
+pub fn restore_terminal() {
+    if !TERMINAL_TAKEN.load(Ordering::SeqCst) {
+        return;
+    }
+
+    if let Ok(original) = ORIGINAL_TERMIOS.try_lock() {
+        if let Some(ref termios) = *original {
+            unsafe {
+                libc::tcsetattr(libc::STDIN_FILENO, libc::TCSANOW, termios);
+            }
+        }
+    }
+
+    let mut stdout = io::stdout();
+    let _ = write!(
+        stdout,
+        "{}{}{}{}{}",
+        RESET_MODES,
+        termion::screen::ToMainScreen,
+        termion::clear::All,
+        termion::cursor::Goto(1, 1),
+        termion::cursor::Show
+    );
+    let _ = stdout.flush();
+}
+
+/// Restores the terminal before the panic message is printed, so it can be
+/// read.
+pub fn install_panic_hook() {
+    let default_hook = panic::take_hook();
+    panic::set_hook(Box::new(move |info| {
+        leave_server();
+        restore_terminal();
+        default_hook(info);
+    }));
+}
+
+/// Cleans up and exits on SIGINT and SIGTERM. Raw mode means Ctrl-C normally
+/// arrives as a key instead, so these come from outside the client.
+pub fn install_signal_handlers() -> Result<()> {
+    let mut signals = Signals::new([SIGINT, SIGTERM])
+        .map_err(Error::terminal("Failed to install signal handlers"))?;
+
+    thread::Builder::new()
+        .name(String::from("signal-handler"))
+        .spawn(move || {
+            if let Some(signal) = signals.forever().next() {
+                leave_server();
+                restore_terminal();
+                process::exit(128 + signal);
+            }
+        })
+        .map_err(Error::terminal("Failed to spawn signal handler"))?;
+
+    Ok(())
+}
@@ -0,0 +1,357 @@
+use std::cell::RefCell;
+use std::collections::HashSet;
+use std::fs;
+use std::path::PathBuf;
+use std::rc::Rc;
+
+use rhai::{self, CallFnOptions, Dynamic, Engine, Scope, AST};
+use serde::Serialize;
+
+use app::Action;
+use config::Config;
+use game::{Entity, Game, GameInformation, Room};
+use protocol::ServerMessage;
+
+// Under the config directory.
+const SCRIPTS_DIR: &str = "scripts";
+const EXTENSION: &str = "rhai";
+
+// Enough for any sensible trigger, and stops one stuck in a loop from
+// freezing the client.
+const MAX_OPERATIONS: u64 = 100_000;
+
+/// Where scripts are loaded from, next to the config file.
+pub fn scripts_dir(config: &Config) -> Option<PathBuf> {
+    config.dir.as_ref().map(|dir| dir.join(SCRIPTS_DIR))
+}
+
+/// What scripts get to see of the game, as the `game` argument of every
+/// hook. It's a copy, so changing it does nothing.
+#[derive(Serialize)]
+struct Snapshot<'a> {
+    player: &'a Entity,
+    room: &'a Room,
+    exits: &'a [Room],
+    entities: &'a [Entity],
+    game: &'a Game,
+}
+
+impl<'a> Snapshot<'a> {
+    fn of(game_info: &'a GameInformation) -> Snapshot<'a> {
+        Snapshot {
+            player: &game_info.player,
+            room: &game_info.current_room,
+            exits: &game_info.adjacent_rooms,
+            entities: &game_info.current_enemies,
+            game: &game_info.game,
+        }
+    }
+}
+
+/// What scripts have asked for while running.
+#[derive(Default)]
+struct Requests {
+    commands: Vec<String>,
+    printed: Vec<String>,
+}
+
+struct Script {
+    name: String,
+    ast: AST,
+}
+
+/// Rhai scripts from the scripts directory that react to the game. Each may
+/// define any of these hooks, which are all passed the `game` snapshot
+/// first:
+///
+/// * `on_message(game, message)` for every message from the server.
+/// * `on_enter(game, entity)` when someone shows up in the player's room.
+/// * `on_room(game, room)` when the player moves.
+/// * `on_health(game, health, previous)` when the player's health changes.
+///
+/// Hooks act by calling `command(line)` with anything that could be typed
+/// into the input section. `print` writes to the feed.
+pub struct Scripts {
+    dir: Option<PathBuf>,
+    engine: Engine,
+    scripts: Vec<Script>,
+    requests: Rc<RefCell<Requests>>,
+    /// Who has been seen in the player's room since they entered it.
+    present: HashSet<String>,
+    health: Option<i16>,
+}
+
+impl Scripts {
+    /// Scripts will be read from `dir`, nothing is loaded until `load`.
+    pub fn new(dir: Option<PathBuf>) -> Scripts {
+        let requests = Rc::new(RefCell::new(Requests::default()));
+        let mut engine = Engine::new();
+        engine.set_max_operations(MAX_OPERATIONS);
+        {
+            let requests = requests.clone();
+            engine.register_fn("command", move |line: &str| {
+                requests.borrow_mut().commands.push(line.to_string());
+            });
+        }
+        {
+            let requests = requests.clone();
+            engine.on_print(move |text| requests.borrow_mut().printed.push(text.to_string()));
+        }
+
+        Scripts {
+            dir,
+            engine,
+            scripts: Vec::new(),
+            requests,
+            present: HashSet::new(),
+            health: None,
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.scripts.is_empty()
+    }
+
+    /// Says what's loaded, for the feed.
+    pub fn summary(&self) -> String {
+        let dir = match self.dir {
+            Some(ref dir) => dir.display().to_string(),
+            None => return String::from("There's no config directory to load scripts from."),
+        };
+        if self.scripts.is_empty() {
+            return format!("No scripts in {}.", dir);
+        }
+        let names: Vec<&str> = self.scripts.iter().map(|script| script.name.as_str()).collect();
+        format!("Loaded {} script(s) from {}: {}.", names.len(), dir, names.join(", "))
+    }
+
+    /// Replaces whatever was loaded with the scripts now in the directory.
+    /// Returns what went wrong along the way, and anything the scripts
+    /// printed or asked to do as they were run for the first time.
+    pub fn load(&mut self) -> Vec<Action> {
+        self.scripts.clear();
+
+        let entries = match self.dir.as_ref().map(fs::read_dir) {
+            Some(Ok(entries)) => entries,
+            // No directory is the same as no scripts.
+            Some(Err(_)) | None => return Vec::new(),
+        };
+        let mut paths: Vec<PathBuf> = entries
+            .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+            .filter(|path| path.extension().is_some_and(|ext| ext == EXTENSION))
+            .collect();
+        paths.sort();
+
+        let mut errors = Vec::new();
+        for path in paths {
+            let name = path
+                .file_name()
+                .map_or_else(String::new, |name| name.to_string_lossy().into_owned());
+            let added = fs::read_to_string(&path)
+                .map_err(|e| e.to_string())
+                .and_then(|source| self.add(name.clone(), &source));
+            if let Err(e) = added {
+                errors.push(Action::Notice(format!("Script {}: {}", name, e)));
+            }
+        }
+        errors.extend(self.take_requests());
+        errors
+    }
+
+    /// Compiles and runs `source`, keeping it for its hooks if that works.
+    fn add(&mut self, name: String, source: &str) -> Result<(), String> {
+        let ast = self.engine.compile(source).map_err(|e| e.to_string())?;
+        self.engine.run_ast(&ast).map_err(|e| e.to_string())?;
+        self.scripts.push(Script { name, ast });
+        Ok(())
+    }
+
+    /// Runs the hooks a packet calls for. The packet has already been
+    /// applied to `game_info`. Returns the commands the scripts issued, and
+    /// notices for what they printed or how they failed.
+    pub fn react(&mut self, msg: &ServerMessage, game_info: &GameInformation) -> Vec<Action> {
+        if self.scripts.is_empty() {
+            return Vec::new();
+        }
+
+        let mut actions = Vec::new();
+        match *msg {
+            ServerMessage::Message(ref message) => {
+                self.call("on_message", game_info, vec![to_dynamic(message)], &mut actions)
+            }
+            ServerMessage::Room(ref room) => {
+                self.present.clear();
+                self.call("on_room", game_info, vec![to_dynamic(room)], &mut actions);
+            }
+            ServerMessage::Character(ref entity) if entity.name == game_info.player.name => {
+                let previous = self.health.replace(entity.health);
+                if let Some(previous) = previous.filter(|previous| *previous != entity.health) {
+                    let args = vec![
+                        Dynamic::from(i64::from(entity.health)),
+                        Dynamic::from(i64::from(previous)),
+                    ];
+                    self.call("on_health", game_info, args, &mut actions);
+                }
+            }
+            ServerMessage::Character(ref entity) => {
+                if entity.location != game_info.current_room.number {
+                    self.present.remove(&entity.name);
+                } else if self.present.insert(entity.name.clone()) {
+                    self.call("on_enter", game_info, vec![to_dynamic(entity)], &mut actions);
+                }
+            }
+            _ => {}
+        }
+        actions
+    }
+
+    fn call(
+        &mut self,
+        hook: &str,
+        game_info: &GameInformation,
+        args: Vec<Dynamic>,
+        actions: &mut Vec<Action>,
+    ) {
+        let game = to_dynamic(&Snapshot::of(game_info));
+        for script in &self.scripts {
+            let defined = script
+                .ast
+                .iter_functions()
+                .any(|f| f.name == hook && f.params.len() == args.len() + 1);
+            if !defined {
+                continue;
+            }
+
+            let mut call_args = vec![game.clone()];
+            call_args.extend(args.iter().cloned());
+            // The top level already ran when the script was loaded.
+            let options = CallFnOptions::new().eval_ast(false);
+            let result = self.engine.call_fn_with_options::<Dynamic>(
+                options,
+                &mut Scope::new(),
+                &script.ast,
+                hook,
+                call_args,
+            );
+            if let Err(e) = result {
+                actions.push(Action::Notice(format!("Script {}: {}", script.name, e)));
+            }
+        }
+        actions.extend(self.take_requests());
+    }
+
+    fn take_requests(&self) -> Vec<Action> {
+        let requests = &mut *self.requests.borrow_mut();
+        let printed = requests.printed.drain(..).map(Action::Notice);
+        let commands = requests.commands.drain(..).map(Action::Submit);
+        printed.chain(commands).collect()
+    }
+}
+
+// Everything passed in derives `Serialize` with plain fields, which always
+// converts.
+fn to_dynamic<T: Serialize>(value: &T) -> Dynamic {
+    rhai::serde::to_dynamic(value).unwrap_or(Dynamic::UNIT)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::PathBuf;
+
+    use chrono::prelude::*;
+
+    use app::Action;
+    use game::{Entity, Game, GameInformation, Message};
+    use protocol::ServerMessage;
+    use super::Scripts;
+
+    fn scripts(source: &str) -> Scripts {
+        let mut scripts = Scripts::new(None);
+        scripts.add(String::from("test.rhai"), source).unwrap();
+        scripts
+    }
+
+    fn says(sender: &str, content: &str) -> ServerMessage {
+        ServerMessage::Message(Message {
+            sender: String::from(sender),
+            receiver: String::from("Alice"),
+            content: String::from(content),
+            received: Local::now(),
+            notice: false,
+        })
+    }
+
+    fn submitted(actions: Vec<Action>) -> Vec<String> {
+        actions
+            .into_iter()
+            .filter_map(|action| match action {
+                Action::Submit(line) => Some(line),
+                _ => None,
+            })
+            .collect()
+    }
+
+    #[test]
+    fn example_script_loads() {
+        let dir = PathBuf::from(concat!(env!("CARGO_MANIFEST_DIR"), "/data"));
+        let mut scripts = Scripts::new(Some(dir));
+        assert!(scripts.load().is_empty());
+        assert!(!scripts.is_empty());
+    }
+
+    #[test]
+    fn messages_trigger_commands() {
+        let mut scripts = scripts(
+            r#"
+            fn on_message(game, message) {
+                if message.content.contains("help") {
+                    command("/tell " + message.sender + " On my way to " + game.room.name);
+                }
+            }
+            "#,
+        );
+        let mut game_info = GameInformation::new(Game::default());
+        game_info.current_room.name = String::from("Vault");
+
+        let actions = scripts.react(&says("Bob", "help me"), &game_info);
+        assert_eq!(submitted(actions), vec!["/tell Bob On my way to Vault"]);
+        assert!(scripts.react(&says("Bob", "hello"), &game_info).is_empty());
+    }
+
+    #[test]
+    fn health_changes_are_reported_once() {
+        let mut scripts = scripts(
+            r#"
+            fn on_health(game, health, previous) {
+                if health < 20 && previous >= 20 { command("/go 1"); }
+            }
+            "#,
+        );
+        let mut game_info = GameInformation::new(Game::default());
+        game_info.player.name = String::from("Alice");
+
+        let mut reactions = Vec::new();
+        for health in &[50, 10, 10, 5] {
+            let player = Entity {
+                name: String::from("Alice"),
+                health: *health,
+                ..Entity::default()
+            };
+            let actions = scripts.react(&ServerMessage::Character(player), &game_info);
+            reactions.extend(submitted(actions));
+        }
+        assert_eq!(reactions, vec!["/go 1"]);
+    }
+
+    #[test]
+    fn script_errors_become_notices() {
+        let mut scripts = scripts("fn on_message(game, message) { undefined_function(); }");
+        let game_info = GameInformation::new(Game::default());
+
+        match scripts.react(&says("Bob", "hi"), &game_info).pop() {
+            Some(Action::Notice(ref notice)) => assert!(notice.starts_with("Script test.rhai: ")),
+            _ => panic!("Expected a notice."),
+        }
+        assert!(Scripts::new(None).add(String::from("bad.rhai"), "fn (").is_err());
+    }
+}
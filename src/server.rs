@@ -0,0 +1,617 @@
+//! A small LURK server for trying the client out and for driving it in
+//! tests. It serves the rooms, monsters and messages of a `World` and keeps
+//! the rules simple: fights are decided in one exchange of blows, monsters
+//! never move and there's no player versus player combat.
+
+use std::convert::TryFrom;
+use std::fs;
+use std::io::{Read, Write};
+use std::net::{SocketAddr, TcpListener, TcpStream, ToSocketAddrs};
+use std::sync::{Arc, Mutex};
+use std::thread;
+
+use liblurk::protocol::protocol_message::{Accept, ChangeRoom, Character, Connection, Error, Game,
+                                          LurkMessageBlobify, LurkMessageParse, Loot, Message,
+                                          PvpFight, Room};
+use toml;
+
+use protocol::{CHANGE_ROOM_TYPE, CHARACTER_TYPE, FIGHT_TYPE, LEAVE_TYPE, LOOT_TYPE, MESSAGE_TYPE,
+               PVP_FIGHT_TYPE, START_TYPE};
+
+const OTHER_ERROR: u8 = 0;
+const BAD_ROOM: u8 = 1;
+const PLAYER_EXISTS: u8 = 2;
+const BAD_MONSTER: u8 = 3;
+const STAT_ERROR: u8 = 4;
+const NOT_READY: u8 = 5;
+const NO_TARGET: u8 = 6;
+const NO_FIGHT: u8 = 7;
+const NO_PVP: u8 = 8;
+
+const STARTING_HEALTH: i16 = 100;
+
+#[derive(Clone, Deserialize)]
+pub struct RoomData {
+    pub number: u16,
+    pub name: String,
+    pub description: String,
+    #[serde(default)]
+    pub exits: Vec<u16>,
+}
+
+#[derive(Clone, Deserialize)]
+pub struct MonsterData {
+    pub name: String,
+    pub room: u16,
+    pub attack: u16,
+    pub defense: u16,
+    pub regen: u16,
+    pub health: i16,
+    pub gold: u16,
+    pub description: String,
+}
+
+/// Sent to every player as they start.
+#[derive(Clone, Deserialize)]
+pub struct Greeting {
+    pub sender: String,
+    pub content: String,
+}
+
+/// Everything the server serves, as read from a TOML data file.
+#[derive(Clone, Deserialize)]
+pub struct World {
+    pub description: String,
+    pub initial_points: u16,
+    pub stat_limit: u16,
+    pub start_room: u16,
+    pub rooms: Vec<RoomData>,
+    #[serde(default)]
+    pub monsters: Vec<MonsterData>,
+    #[serde(default)]
+    pub messages: Vec<Greeting>,
+}
+
+impl World {
+    pub fn parse(text: &str) -> Result<World, String> {
+        let world: World = toml::from_str(text).map_err(|e| format!("Invalid world: {}", e))?;
+
+        let known = |number: u16| world.rooms.iter().any(|room| room.number == number);
+        if !known(world.start_room) {
+            return Err(format!("The start room #{} doesn't exist.", world.start_room));
+        }
+        for room in &world.rooms {
+            if let Some(exit) = room.exits.iter().find(|exit| !known(**exit)) {
+                return Err(format!("{} has an exit to missing room #{}.", room.name, exit));
+            }
+        }
+        for monster in &world.monsters {
+            if !known(monster.room) {
+                return Err(format!("{} is in missing room #{}.", monster.name, monster.room));
+            }
+        }
+
+        Ok(world)
+    }
+
+    pub fn load(path: &str) -> Result<World, String> {
+        let text = fs::read_to_string(path)
+            .map_err(|e| format!("Failed to read world file {}: {}", path, e))?;
+        World::parse(&text)
+    }
+
+    /// The world in `data/world.toml`.
+    pub fn example() -> World {
+        World::parse(include_str!("../data/world.toml")).expect("The example world is invalid.")
+    }
+
+    fn room(&self, number: u16) -> Option<&RoomData> {
+        self.rooms.iter().find(|room| room.number == number)
+    }
+}
+
+/// A player or monster.
+#[derive(Clone)]
+struct Being {
+    name: String,
+    description: String,
+    is_monster: bool,
+    is_alive: bool,
+    join_battle: bool,
+    started: bool,
+    attack: u16,
+    defense: u16,
+    regen: u16,
+    health: i16,
+    gold: u16,
+    room: u16,
+}
+
+impl Being {
+    fn character(&self) -> Character {
+        Character {
+            player_name: self.name.clone(),
+            is_alive: self.is_alive,
+            join_battle: self.join_battle,
+            is_monster: self.is_monster,
+            is_started: self.started,
+            is_ready: true,
+            attack: self.attack,
+            defense: self.defense,
+            regen: self.regen,
+            health: self.health,
+            gold: self.gold,
+            current_room: self.room,
+            description: self.description.clone(),
+        }
+    }
+
+    fn take_hit(&mut self, attack: u16) {
+        let damage = attack.saturating_sub(self.defense).max(1);
+        let damage = i16::try_from(damage).unwrap_or(i16::MAX);
+        self.health = self.health.saturating_sub(damage);
+        if self.health <= 0 {
+            self.health = 0;
+            self.is_alive = false;
+        }
+    }
+}
+
+struct Player {
+    being: Being,
+    stream: TcpStream,
+}
+
+enum Request {
+    Message(Message),
+    ChangeRoom(u16),
+    Fight,
+    PvpFight,
+    Loot(String),
+    Start,
+    Character(Character),
+    Leave,
+}
+
+fn parse<T: LurkMessageParse<T>>(stream: &mut TcpStream, name: &str) -> Result<T, String> {
+    T::parse_lurk_message_from_stream(stream)
+        .map_err(|_| format!("Failed to parse {} packet.", name))
+}
+
+fn read_request(stream: &mut TcpStream) -> Result<Request, String> {
+    let mut kind = [0u8; 1];
+    stream
+        .read_exact(&mut kind)
+        .map_err(|e| format!("Failed to read packet type: {}", e))?;
+
+    match kind[0] {
+        MESSAGE_TYPE => Ok(Request::Message(parse(stream, "MESSAGE")?)),
+        CHANGE_ROOM_TYPE => {
+            let change: ChangeRoom = parse(stream, "CHANGEROOM")?;
+            Ok(Request::ChangeRoom(change.room_number))
+        }
+        FIGHT_TYPE => Ok(Request::Fight),
+        PVP_FIGHT_TYPE => {
+            // Always refused, so who it was aimed at doesn't matter.
+            let _: PvpFight = parse(stream, "PVPFIGHT")?;
+            Ok(Request::PvpFight)
+        }
+        LOOT_TYPE => {
+            let loot: Loot = parse(stream, "LOOT")?;
+            Ok(Request::Loot(loot.target))
+        }
+        START_TYPE => Ok(Request::Start),
+        CHARACTER_TYPE => Ok(Request::Character(parse(stream, "CHARACTER")?)),
+        LEAVE_TYPE => Ok(Request::Leave),
+        other => Err(format!("Client sent unexpected packet type {}.", other)),
+    }
+}
+
+// A client that has gone away is cleaned up by its own thread, so failed
+// writes are ignored here.
+fn send<M: LurkMessageBlobify>(mut stream: &TcpStream, msg: &M) {
+    let _ = stream.write_all(&msg.produce_lurk_message_blob());
+}
+
+fn refuse(stream: &TcpStream, code: u8, message: &str) {
+    send(
+        stream,
+        &Error {
+            error_code: code,
+            message: message.to_string(),
+        },
+    );
+}
+
+struct State {
+    world: World,
+    monsters: Vec<Being>,
+    players: Vec<Player>,
+}
+
+impl State {
+    fn new(world: World) -> State {
+        let monsters = world
+            .monsters
+            .iter()
+            .map(|monster| Being {
+                name: monster.name.clone(),
+                description: monster.description.clone(),
+                is_monster: true,
+                is_alive: monster.health > 0,
+                join_battle: true,
+                started: true,
+                attack: monster.attack,
+                defense: monster.defense,
+                regen: monster.regen,
+                health: monster.health,
+                gold: monster.gold,
+                room: monster.room,
+            })
+            .collect();
+
+        State {
+            world,
+            monsters,
+            players: Vec::new(),
+        }
+    }
+
+    fn player(&self, name: &str) -> Option<&Player> {
+        self.players.iter().find(|player| player.being.name == name)
+    }
+
+    fn player_mut(&mut self, name: &str) -> Option<&mut Player> {
+        self.players.iter_mut().find(|player| player.being.name == name)
+    }
+
+    /// Sends `being` to every started player in `room`.
+    fn announce(&self, being: &Being, room: u16) {
+        let character = being.character();
+        for player in &self.players {
+            if player.being.started && player.being.room == room {
+                send(&player.stream, &character);
+            }
+        }
+    }
+
+    /// Everyone in `room` other than the player called `except`.
+    fn occupants(&self, room: u16, except: &str) -> Vec<Being> {
+        self.monsters
+            .iter()
+            .chain(self.players.iter().map(|player| &player.being))
+            .filter(|being| being.room == room && being.started && being.name != except)
+            .cloned()
+            .collect()
+    }
+
+    fn enter_room(&mut self, name: &str, number: u16) {
+        let old_room = match self.player_mut(name) {
+            Some(player) => {
+                let old_room = player.being.room;
+                player.being.room = number;
+                old_room
+            }
+            None => return,
+        };
+
+        let being = self.player(name).unwrap().being.clone();
+        let stream = &self.player(name).unwrap().stream;
+        let room = self.world.room(number).unwrap();
+
+        send(
+            stream,
+            &Room {
+                room_number: room.number,
+                room_name: room.name.clone(),
+                description: room.description.clone(),
+            },
+        );
+        for other in self.occupants(number, name) {
+            send(stream, &other.character());
+        }
+        for exit in room.exits.iter().filter_map(|exit| self.world.room(*exit)) {
+            send(
+                stream,
+                &Connection {
+                    room_number: exit.number,
+                    room_name: exit.name.clone(),
+                    description: exit.description.clone(),
+                },
+            );
+        }
+
+        if old_room != number {
+            self.announce(&being, old_room);
+        }
+        self.announce(&being, number);
+    }
+
+    fn handle(&mut self, name: &mut Option<String>, stream: &TcpStream, request: Request) {
+        let playing = match *name {
+            Some(ref name) => name.clone(),
+            None => {
+                return match request {
+                    Request::Character(character) => self.create(name, stream, character),
+                    Request::Leave => {}
+                    _ => refuse(stream, NOT_READY, "Send a character first."),
+                }
+            }
+        };
+        let started = self.player(&playing).is_some_and(|p| p.being.started);
+
+        match request {
+            Request::Character(_) => refuse(stream, OTHER_ERROR, "You already have a character."),
+            Request::Start if started => refuse(stream, OTHER_ERROR, "You've already started."),
+            Request::Start => self.start(&playing),
+            Request::Leave => {}
+            _ if !started => refuse(stream, NOT_READY, "Send START first."),
+            Request::ChangeRoom(number) => self.change_room(&playing, stream, number),
+            Request::Fight => self.fight(&playing, stream),
+            Request::PvpFight => refuse(stream, NO_PVP, "Players can't fight each other here."),
+            Request::Loot(target) => self.loot(&playing, stream, &target),
+            Request::Message(msg) => self.message(&playing, stream, msg),
+        }
+    }
+
+    fn create(&mut self, name: &mut Option<String>, stream: &TcpStream, character: Character) {
+        let taken = self.player(&character.player_name).is_some()
+            || self.monsters.iter().any(|m| m.name == character.player_name);
+        if character.player_name.is_empty() || taken {
+            return refuse(stream, PLAYER_EXISTS, "That name is already taken.");
+        }
+
+        let stats = [character.attack, character.defense, character.regen];
+        let spent: u32 = stats.iter().map(|stat| u32::from(*stat)).sum();
+        let allowed = self.world.initial_points.min(self.world.stat_limit);
+        if spent > u32::from(allowed) {
+            return refuse(stream, STAT_ERROR, &format!("Stats may total at most {}.", allowed));
+        }
+
+        let being = Being {
+            name: character.player_name.clone(),
+            description: character.description,
+            is_monster: false,
+            is_alive: true,
+            join_battle: character.join_battle,
+            started: false,
+            attack: character.attack,
+            defense: character.defense,
+            regen: character.regen,
+            health: STARTING_HEALTH,
+            gold: 0,
+            room: self.world.start_room,
+        };
+
+        send(
+            stream,
+            &Accept {
+                accept_type: CHARACTER_TYPE,
+            },
+        );
+        send(stream, &being.character());
+
+        let stream = match stream.try_clone() {
+            Ok(stream) => stream,
+            Err(_) => return refuse(stream, OTHER_ERROR, "The server couldn't keep up."),
+        };
+        *name = Some(being.name.clone());
+        self.players.push(Player { being, stream });
+    }
+
+    fn start(&mut self, name: &str) {
+        let start_room = self.world.start_room;
+        if let Some(player) = self.player_mut(name) {
+            player.being.started = true;
+        }
+
+        let greetings = self.world.messages.clone();
+        if let Some(player) = self.player(name) {
+            for greeting in greetings {
+                send(
+                    &player.stream,
+                    &Message {
+                        receiver: name.to_string(),
+                        sender: greeting.sender,
+                        message: greeting.content,
+                    },
+                );
+            }
+        }
+
+        self.enter_room(name, start_room);
+    }
+
+    fn change_room(&mut self, name: &str, stream: &TcpStream, number: u16) {
+        let here = self.player(name).unwrap().being.room;
+        let connected = self.world
+            .room(here)
+            .is_some_and(|room| room.exits.contains(&number));
+        if !connected {
+            return refuse(stream, BAD_ROOM, &format!("No exit leads to room #{}.", number));
+        }
+        self.enter_room(name, number);
+    }
+
+    /// Everyone in the fight swings once at every monster, then every
+    /// monster left standing swings once at each of them.
+    fn fight(&mut self, name: &str, stream: &TcpStream) {
+        let room = self.player(name).unwrap().being.room;
+        if !self.player(name).unwrap().being.is_alive {
+            return refuse(stream, OTHER_ERROR, "You're in no state to fight.");
+        }
+        if !self.monsters.iter().any(|m| m.room == room && m.is_alive) {
+            return refuse(stream, NO_FIGHT, "There's nothing here to fight.");
+        }
+
+        let fighters: Vec<String> = self.players
+            .iter()
+            .map(|player| &player.being)
+            .filter(|being| being.room == room && being.started && being.is_alive)
+            .filter(|being| being.name == name || being.join_battle)
+            .map(|being| being.name.clone())
+            .collect();
+
+        for fighter in &fighters {
+            let attack = self.player(fighter).unwrap().being.attack;
+            for monster in self.monsters.iter_mut().filter(|m| m.room == room && m.is_alive) {
+                monster.take_hit(attack);
+            }
+        }
+        let monsters: Vec<Being> = self.monsters
+            .iter()
+            .filter(|m| m.room == room)
+            .cloned()
+            .collect();
+        for monster in monsters.iter().filter(|m| m.is_alive) {
+            for fighter in &fighters {
+                if let Some(player) = self.player_mut(fighter) {
+                    player.being.take_hit(monster.attack);
+                }
+            }
+        }
+
+        for monster in &monsters {
+            self.announce(monster, room);
+        }
+        for fighter in &fighters {
+            let being = self.player(fighter).unwrap().being.clone();
+            self.announce(&being, room);
+        }
+    }
+
+    fn loot(&mut self, name: &str, stream: &TcpStream, target: &str) {
+        let room = self.player(name).unwrap().being.room;
+
+        let gold = if let Some(monster) = self.monsters
+            .iter_mut()
+            .find(|m| m.name == target && m.room == room)
+        {
+            if monster.is_alive {
+                return refuse(stream, BAD_MONSTER, &format!("{} is still alive.", target));
+            }
+            let gold = monster.gold;
+            monster.gold = 0;
+            gold
+        } else if let Some(player) = self.players
+            .iter_mut()
+            .find(|p| p.being.name == target && p.being.room == room && p.being.started)
+        {
+            if player.being.is_alive {
+                return refuse(stream, BAD_MONSTER, &format!("{} is still alive.", target));
+            }
+            let gold = player.being.gold;
+            player.being.gold = 0;
+            gold
+        } else {
+            return refuse(stream, NO_TARGET, &format!("There's no {} here to loot.", target));
+        };
+
+        if let Some(player) = self.player_mut(name) {
+            player.being.gold = player.being.gold.saturating_add(gold);
+        }
+
+        let looted = self.occupants(room, "")
+            .into_iter()
+            .filter(|being| being.name == target || being.name == name);
+        for being in looted {
+            self.announce(&being, room);
+        }
+    }
+
+    fn message(&mut self, name: &str, stream: &TcpStream, msg: Message) {
+        let receiver = match self.player(&msg.receiver) {
+            Some(receiver) => receiver,
+            None => {
+                return refuse(
+                    stream,
+                    NO_TARGET,
+                    &format!("Nobody called {} is playing.", msg.receiver),
+                )
+            }
+        };
+        send(
+            &receiver.stream,
+            &Message {
+                receiver: msg.receiver.clone(),
+                sender: name.to_string(),
+                message: msg.message,
+            },
+        );
+    }
+
+    fn remove(&mut self, name: &str) {
+        if let Some(index) = self.players.iter().position(|p| p.being.name == name) {
+            let mut being = self.players.remove(index).being;
+            being.started = false;
+            self.announce(&being, being.room);
+        }
+    }
+}
+
+fn serve(state: Arc<Mutex<State>>, mut stream: TcpStream) {
+    {
+        let state = state.lock().expect("Failed to lock server state.");
+        send(
+            &stream,
+            &Game {
+                initial_points: state.world.initial_points,
+                stat_limit: state.world.stat_limit,
+                game_description: state.world.description.clone(),
+            },
+        );
+    }
+
+    let mut name = None;
+    while let Ok(request) = read_request(&mut stream) {
+        let leaving = matches!(request, Request::Leave);
+
+        state
+            .lock()
+            .expect("Failed to lock server state.")
+            .handle(&mut name, &stream, request);
+
+        if leaving {
+            break;
+        }
+    }
+
+    if let Some(name) = name {
+        state.lock().expect("Failed to lock server state.").remove(&name);
+    }
+}
+
+pub struct Server {
+    listener: TcpListener,
+    state: Arc<Mutex<State>>,
+}
+
+impl Server {
+    pub fn bind<A: ToSocketAddrs>(addr: A, world: World) -> Result<Server, String> {
+        let listener = TcpListener::bind(addr).map_err(|e| format!("Failed to listen: {}", e))?;
+        Ok(Server {
+            listener,
+            state: Arc::new(Mutex::new(State::new(world))),
+        })
+    }
+
+    pub fn local_addr(&self) -> Result<SocketAddr, String> {
+        self.listener
+            .local_addr()
+            .map_err(|e| format!("Failed to get the listening address: {}", e))
+    }
+
+    /// Serves every client on its own thread, forever.
+    pub fn run(self) {
+        for stream in self.listener.incoming() {
+            let stream = match stream {
+                Ok(stream) => stream,
+                Err(_) => continue,
+            };
+            let state = self.state.clone();
+            let _ = thread::Builder::new()
+                .name(String::from("lurk-mock-client"))
+                .spawn(move || serve(state, stream));
+        }
+    }
+}
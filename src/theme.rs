@@ -0,0 +1,132 @@
+use tui::style::Color;
+
+/// The colors everything is drawn with.
+#[derive(Clone, Copy, PartialEq)]
+pub struct Theme {
+    pub border: Color,
+    pub background: Color,
+    pub text: Color,
+    /// Private messages.
+    pub whisper: Color,
+    /// Damage, health and anything that went wrong.
+    pub danger: Color,
+    /// Defense and other neutral highlights.
+    pub info: Color,
+    /// Regeneration and healing.
+    pub good: Color,
+    pub gold: Color,
+    /// Things that are less important, like unexplored rooms and the dead.
+    pub muted: Color,
+}
+
+/// The built in themes, in the order they're cycled through.
+pub const PRESETS: [&str; 4] = ["classic", "solarized", "high-contrast", "monochrome"];
+
+impl Default for Theme {
+    fn default() -> Theme {
+        Theme {
+            border: Color::Green,
+            background: Color::Black,
+            text: Color::White,
+            whisper: Color::Magenta,
+            danger: Color::Red,
+            info: Color::Cyan,
+            good: Color::LightGreen,
+            gold: Color::Yellow,
+            muted: Color::DarkGray,
+        }
+    }
+}
+
+impl Theme {
+    /// One of `PRESETS`, by name.
+    pub fn preset(name: &str) -> Option<Theme> {
+        match name.to_lowercase().as_str() {
+            "classic" => Some(Theme::default()),
+            "solarized" => Some(Theme {
+                border: Color::Rgb(0x26, 0x8b, 0xd2),
+                background: Color::Rgb(0x00, 0x2b, 0x36),
+                text: Color::Rgb(0x93, 0xa1, 0xa1),
+                whisper: Color::Rgb(0xd3, 0x36, 0x82),
+                danger: Color::Rgb(0xdc, 0x32, 0x2f),
+                info: Color::Rgb(0x2a, 0xa1, 0x98),
+                good: Color::Rgb(0x85, 0x99, 0x00),
+                gold: Color::Rgb(0xb5, 0x89, 0x00),
+                muted: Color::Rgb(0x58, 0x6e, 0x75),
+            }),
+            "high-contrast" => Some(Theme {
+                border: Color::White,
+                background: Color::Black,
+                text: Color::White,
+                whisper: Color::LightMagenta,
+                danger: Color::LightRed,
+                info: Color::LightCyan,
+                good: Color::LightGreen,
+                gold: Color::LightYellow,
+                muted: Color::Gray,
+            }),
+            // Leaves every color to the terminal, highlights still show since
+            // they're drawn with modifiers.
+            "monochrome" => Some(Theme {
+                border: Color::Reset,
+                background: Color::Reset,
+                text: Color::Reset,
+                whisper: Color::Reset,
+                danger: Color::Reset,
+                info: Color::Reset,
+                good: Color::Reset,
+                gold: Color::Reset,
+                muted: Color::Reset,
+            }),
+            _ => None,
+        }
+    }
+}
+
+pub fn unknown_preset(name: &str) -> String {
+    format!("Unknown theme '{}'. Themes: {}.", name, PRESETS.join(", "))
+}
+
+/// The preset after `name`, starting over from the first.
+pub fn next_preset(name: &str) -> &'static str {
+    match PRESETS.iter().position(|preset| *preset == name) {
+        Some(index) => PRESETS[(index + 1) % PRESETS.len()],
+        None => PRESETS[0],
+    }
+}
+
+/// Reads a color as written in the config file, either one of the terminal's
+/// named colors or `#rrggbb`.
+pub fn parse_color(name: &str) -> Result<Color, String> {
+    let normalized = name.trim()
+        .to_lowercase()
+        .replace(['_', '-', ' '], "");
+
+    if normalized.starts_with('#') && normalized.len() == 7 && normalized.is_ascii() {
+        let channel = |i: usize| u8::from_str_radix(&normalized[i..i + 2], 16);
+        if let (Ok(r), Ok(g), Ok(b)) = (channel(1), channel(3), channel(5)) {
+            return Ok(Color::Rgb(r, g, b));
+        }
+    }
+
+    Ok(match normalized.as_str() {
+        "reset" | "default" => Color::Reset,
+        "black" => Color::Black,
+        "red" => Color::Red,
+        "green" => Color::Green,
+        "yellow" => Color::Yellow,
+        "blue" => Color::Blue,
+        "magenta" => Color::Magenta,
+        "cyan" => Color::Cyan,
+        "gray" | "grey" => Color::Gray,
+        "darkgray" | "darkgrey" => Color::DarkGray,
+        "lightred" => Color::LightRed,
+        "lightgreen" => Color::LightGreen,
+        "lightyellow" => Color::LightYellow,
+        "lightblue" => Color::LightBlue,
+        "lightmagenta" => Color::LightMagenta,
+        "lightcyan" => Color::LightCyan,
+        "white" => Color::White,
+        _ => return Err(format!("Unknown color '{}'.", name)),
+    })
+}
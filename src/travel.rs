@@ -0,0 +1,152 @@
+use std::collections::VecDeque;
+
+use chrono::prelude::*;
+
+use command::RoomTarget;
+use game::GameInformation;
+use protocol::ServerMessage;
+
+/// What a server message means for a journey in progress.
+pub enum Progress {
+    Waiting,
+    /// Arrived in the expected room, this is the next one to ask for.
+    Step(u16),
+    Arrived,
+    Stopped(String),
+}
+
+/// A walk across several rooms, one CHANGEROOM at a time.
+pub struct Journey {
+    pub destination: String,
+    route: VecDeque<u16>,
+    set_off: DateTime<Local>,
+}
+
+impl Journey {
+    /// Finds the shortest known route from the current room to `target`.
+    pub fn plan(game_info: &GameInformation, target: &RoomTarget) -> Result<Journey, String> {
+        let map = &game_info.map;
+        let number = match *target {
+            RoomTarget::Number(number) if map.rooms.contains_key(&number) => number,
+            RoomTarget::Number(number) => {
+                return Err(format!("Room #{} hasn't been discovered yet.", number))
+            }
+            RoomTarget::Name(ref name) => map
+                .find(name)
+                .ok_or_else(|| format!("No discovered room is named '{}'.", name))?,
+        };
+        let destination = map.rooms[&number].room.name.clone();
+
+        let here = game_info.current_room.number;
+        if number == here {
+            return Err(format!("You're already in {}.", destination));
+        }
+
+        match map.path(here, number) {
+            Some(route) => Ok(Journey {
+                destination,
+                route: VecDeque::from(route),
+                set_off: Local::now(),
+            }),
+            None => Err(format!("No known route to {}.", destination)),
+        }
+    }
+
+    /// The room asked for but not yet reached.
+    pub fn next_step(&self) -> Option<u16> {
+        self.route.front().cloned()
+    }
+
+    pub fn remaining(&self) -> usize {
+        self.route.len()
+    }
+
+    /// Called with every packet once it's been applied to `game_info`.
+    pub fn advance(&mut self, msg: &ServerMessage, game_info: &GameInformation) -> Progress {
+        match *msg {
+            ServerMessage::Room(ref room) => {
+                if Some(room.number) != self.next_step() {
+                    return Progress::Stopped(format!("ended up in {} instead.", room.name));
+                }
+                self.route.pop_front();
+                match self.next_step() {
+                    Some(next) => Progress::Step(next),
+                    None => Progress::Arrived,
+                }
+            }
+            ServerMessage::Error { ref message, .. } => Progress::Stopped(message.clone()),
+            ServerMessage::Character(_)
+                if game_info.combat.in_fight() || game_info.combat.blood_since(self.set_off) =>
+            {
+                Progress::Stopped(String::from("a fight broke out."))
+            }
+            _ => Progress::Waiting,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use command::RoomTarget;
+    use game::{Game, GameInformation, Room};
+    use protocol::ServerMessage;
+    use super::{Journey, Progress};
+
+    fn room(number: u16) -> Room {
+        Room {
+            name: format!("Room {}", number),
+            description: String::new(),
+            number,
+        }
+    }
+
+    /// Standing in room 1 of the corridor 1 -> 2 -> 3.
+    fn game_info() -> GameInformation {
+        let mut game_info = GameInformation::new(Game::default());
+        game_info.current_room = room(1);
+        game_info.map.visit(&room(1));
+        game_info.map.connect(1, &room(2));
+        game_info.map.connect(2, &room(3));
+        game_info
+    }
+
+    #[test]
+    fn plan_follows_the_shortest_route() {
+        let target = RoomTarget::Name(String::from("room 3"));
+        let journey = Journey::plan(&game_info(), &target).unwrap();
+        assert_eq!(journey.destination, "Room 3");
+        assert_eq!(journey.next_step(), Some(2));
+        assert_eq!(journey.remaining(), 2);
+
+        let error = Journey::plan(&game_info(), &RoomTarget::Number(1)).err();
+        assert_eq!(error, Some(String::from("You're already in Room 1.")));
+    }
+
+    #[test]
+    fn an_error_stops_the_journey() {
+        let game_info = game_info();
+        let mut journey = Journey::plan(&game_info, &RoomTarget::Number(3)).unwrap();
+        let error = ServerMessage::Error {
+            code: 1,
+            message: String::from("The door is locked."),
+        };
+        match journey.advance(&error, &game_info) {
+            Progress::Stopped(reason) => assert_eq!(reason, "The door is locked."),
+            _ => panic!("the journey should have stopped"),
+        }
+    }
+
+    #[test]
+    fn ending_up_elsewhere_stops_the_journey() {
+        let game_info = game_info();
+        let mut journey = Journey::plan(&game_info, &RoomTarget::Number(3)).unwrap();
+        match journey.advance(&ServerMessage::Room(room(2)), &game_info) {
+            Progress::Step(next) => assert_eq!(next, 3),
+            _ => panic!("the journey should have carried on to room 3"),
+        }
+        match journey.advance(&ServerMessage::Room(room(1)), &game_info) {
+            Progress::Stopped(reason) => assert_eq!(reason, "ended up in Room 1 instead."),
+            _ => panic!("the journey should have stopped"),
+        }
+    }
+}
@@ -1,84 +1,137 @@
 use tui::Terminal;
-use tui::widgets::{BarChart, Block, Borders, Gauge, Item, List, Paragraph, SelectableList, Table,
-                   Widget};
+use tui::buffer::Buffer;
+use tui::widgets::{Block, Borders, Gauge, Item, List, Paragraph, Tabs, Widget};
 use tui::layout::{Direction, Group, Rect, Size};
 use tui::style::{Color, Modifier, Style};
-use tui::backend::MouseBackend;
+use tui::backend::{Backend, MouseBackend};
 
 use chrono::prelude::*;
 
-use game::GameInformation;
+use app::{App, FeedLayout, FeedView, Focus, InfoTab, Screen};
+use combat::{CombatEvent, CombatLog};
+use map::RoomMap;
+use net::Status;
+use notification::Notification;
+use theme::Theme;
+use error::{Error, Result};
+use character::{CharacterForm, Field, FormStatus};
+use game::{Entity, Game, GameInformation, Message, Room};
+use input::InputLine;
+use keys::{key_name, KeyBindings};
 
+use std::cell::Cell;
+use std::collections::HashMap;
+use std::io::{self, Write};
 use std::sync::*;
 
-const BORDER_COLOR: Color = Color::Green;
-const BACKGROUND_COLOR: Color = Color::Black;
-const STANDARD_TEXT_COLOR: Color = Color::White;
+// Asks the terminal to wrap pasted text in markers so newlines in it don't
+// submit the input line.
+const ENABLE_BRACKETED_PASTE: &str = "\x1b[?2004h";
+const DISABLE_BRACKETED_PASTE: &str = "\x1b[?2004l";
 
-pub struct TerminalInterface {
-    term: Terminal<MouseBackend>,
+pub struct TerminalInterface<B: Backend> {
+    term: Terminal<B>,
     size: Rect,
     game_data: Arc<Mutex<GameInformation>>,
+    room_list_area: Rect,
+    room_list_offset: usize,
+    feed_layout: FeedLayout,
+    bracketed_paste: bool,
 }
 
-impl TerminalInterface {
-    pub fn new(game_data: Arc<Mutex<GameInformation>>) -> Result<TerminalInterface, String> {
+impl TerminalInterface<MouseBackend> {
+    pub fn new(game_data: Arc<Mutex<GameInformation>>) -> Result<Self> {
         let backend = MouseBackend::new()
-            .map_err(|_| String::from("Failed to create terminal interface backend."))?;
+            .map_err(Error::terminal("Failed to create terminal interface backend"))?;
+        let mut interface = TerminalInterface::with_backend(backend, game_data)?;
+        set_bracketed_paste(true).map_err(Error::terminal("Failed to enable bracketed paste"))?;
+        interface.bracketed_paste = true;
+        Ok(interface)
+    }
+}
+
+impl<B: Backend> TerminalInterface<B> {
+    pub fn with_backend(backend: B, game_data: Arc<Mutex<GameInformation>>) -> Result<Self> {
         let mut term = Terminal::new(backend)
-            .map_err(|_| String::from("Failed to create terminal interface."))?;
+            .map_err(Error::terminal("Failed to create terminal interface"))?;
         let term_size = term.size()
-            .map_err(|_| String::from("Failed to get terminal size."))?;
+            .map_err(Error::terminal("Failed to get terminal size"))?;
         term.clear()
-            .map_err(|_| String::from("Failed to clear terminal."))?;
+            .map_err(Error::terminal("Failed to clear terminal"))?;
         term.hide_cursor()
-            .map_err(|_| String::from("Failed to hide cursor."))?;
+            .map_err(Error::terminal("Failed to hide cursor"))?;
 
         Ok(TerminalInterface {
             term,
             size: term_size,
             game_data,
+            room_list_area: Rect::default(),
+            room_list_offset: 0,
+            feed_layout: FeedLayout::default(),
+            bracketed_paste: false,
         })
     }
 
-    fn base_render(&mut self) {
+    fn base_render(&mut self, theme: &Theme) {
         Block::default()
             .borders(Borders::ALL)
-            .style(Style::default().bg(BACKGROUND_COLOR))
-            .border_style(Style::default().bg(BACKGROUND_COLOR).fg(BORDER_COLOR))
+            .style(Style::default().bg(theme.background))
+            .border_style(Style::default().bg(theme.background).fg(theme.border))
             .render(&mut self.term, &self.size);
     }
 
-    pub fn render(&mut self) -> Result<(), String> {
-        self.base_render();
+    pub fn render(&mut self, app: &App) -> Result<()> {
+        self.render_at(app, Local::now())
+    }
+
+    /// Renders with the clock showing `now`.
+    pub fn render_at(&mut self, app: &App, now: DateTime<Local>) -> Result<()> {
+        let theme = &app.theme;
+        self.base_render(theme);
 
         let game_info = self.game_data.lock().expect("Failed to lock game data.");
 
+        let room_list_area = Cell::new(Rect::default());
+        let feed_layout = Cell::new(FeedLayout::default());
+        let selected_room = app.selected_room
+            .min(game_info.adjacent_rooms.len().saturating_sub(1));
+
+        if let Screen::CreateCharacter(ref form) = app.screen {
+            render_character_form(&mut self.term, &self.size, form, &game_info.game, theme);
+            return self.term.draw().map_err(Error::terminal("Failed to render"));
+        }
+
         // It's a lot easier to just clone these guys out
-        let messages = game_info.messages.clone();
         let player = game_info.player.clone();
+        let entities = game_info.current_enemies.clone();
 
-        let current_datetime: DateTime<Local> = Local::now();
-        let (ampm_status, hour) = current_datetime.hour12();
+        let current_datetime = now;
+        let (is_pm, hour) = current_datetime.hour12();
+        let ampm = if is_pm { "PM" } else { "AM" };
+        let clock = format!(
+            "{:0width$}/{:0width$}/{} - {:?}, {:0width$}:{:0width$}:{:0width$} {}",
+            current_datetime.day(),
+            current_datetime.month(),
+            current_datetime.year(),
+            current_datetime.weekday(),
+            hour,
+            current_datetime.minute(),
+            current_datetime.second(),
+            ampm,
+            width = 2,
+        );
 
-        let ampm = if ampm_status {
-            "AM"
-        }
-        else {
-            "PM"
-        };
-
-        let message_style = Style::default()
-            .bg(BACKGROUND_COLOR)
-            .fg(STANDARD_TEXT_COLOR);
+        // Its borders take two rows, so anything less hides the status.
+        let top_bar_height =
+            (u32::from(self.size.height) * u32::from(app.panels.top_bar) / 100).max(3) as u16;
 
         Group::default()
             .direction(Direction::Vertical)
             .sizes(&[
-                Size::Percent(10),
-                Size::Percent(55),
-                Size::Percent(30),
-                Size::Percent(5),
+                Size::Fixed(top_bar_height),
+                Size::Percent(app.panels.info),
+                Size::Percent(app.panels.bottom),
+                Size::Fixed(3),
             ])
             .render(&mut self.term, &self.size, |term, chunks| {
                 // TOP BAR SECTION
@@ -88,35 +141,36 @@ impl TerminalInterface {
                     .render(term, &chunks[0], |term, chunks| {
                         Group::default()
                             .direction(Direction::Horizontal)
-                            .sizes(&[Size::Percent(80), Size::Percent(20)])
+                            .sizes(&[Size::Min(0), Size::Fixed(clock.chars().count() as u16)])
                             .render(term, &chunks[0], |term, chunks| {
-                                // Left
-                                Block::default()
-                                    .style(Style::default().bg(BACKGROUND_COLOR))
+                                // Connection Status
+                                let status = &app.connection_status;
+                                Paragraph::default()
+                                    .style(
+                                        Style::default()
+                                            .bg(theme.background)
+                                            .fg(status_color(status, theme)),
+                                    )
+                                    .block(Block::default().borders(Borders::TOP | Borders::BOTTOM)
+                                        .style(Style::default().bg(theme.background))
+                                        .border_style(Style::default().bg(theme.background).fg(theme.border)))
+                                    .wrap(true)
+                                    .raw(true)
+                                    .text(&status.describe(now))
                                     .render(term, &chunks[0]);
 
                                 // Date Time Display
                                 Paragraph::default()
                                     .style(
                                         Style::default()
-                                            .bg(BACKGROUND_COLOR)
-                                            .fg(STANDARD_TEXT_COLOR),
+                                            .bg(theme.background)
+                                            .fg(theme.text),
                                     )
                                     .block(Block::default().borders(Borders::TOP | Borders::BOTTOM)
-                                        .style(Style::default().bg(BACKGROUND_COLOR))
-                                        .border_style(Style::default().bg(BACKGROUND_COLOR).fg(BORDER_COLOR)))
-                                    .text(&format!(
-                                        "{:0width$}/{:0width$}/{} - {:?}, {:0width$}:{:0width$}:{:0width$} {}",
-                                        current_datetime.day(),
-                                        current_datetime.month(),
-                                        current_datetime.year(),
-                                        current_datetime.weekday(),
-                                        hour,
-                                        current_datetime.minute(),
-                                        current_datetime.second(),
-                                        ampm,
-                                        width = 2,
-                                    ))
+                                        .style(Style::default().bg(theme.background))
+                                        .border_style(Style::default().bg(theme.background).fg(theme.border)))
+                                    .wrap(true)
+                                    .text(&clock)
                                     .render(term, &chunks[1]);
                             });
                     });
@@ -126,17 +180,70 @@ impl TerminalInterface {
                     .direction(Direction::Horizontal)
                     .sizes(&[Size::Percent(100)])
                     .render(term, &chunks[1], |term, chunks| {
-                        Block::default()
+                        let mut info_block = Block::default()
                             .borders(Borders::BOTTOM)
-                            .border_style(Style::default().bg(BACKGROUND_COLOR).fg(BORDER_COLOR))
-                            .style(Style::default().bg(BACKGROUND_COLOR))
-                            .render(term, &chunks[0]);
+                            .border_style(Style::default().bg(theme.background).fg(theme.border))
+                            .style(Style::default().bg(theme.background));
+                        let inner = info_block.inner(&chunks[0]);
+                        info_block.render(term, &chunks[0]);
+
+                        Group::default()
+                            .direction(Direction::Vertical)
+                            .sizes(&[Size::Fixed(1), Size::Min(0)])
+                            .render(term, &inner, |term, chunks| {
+                                let tab_titles = app.keys.tab_titles();
+                                Tabs::default()
+                                    .titles(&tab_titles)
+                                    .select(app.info_tab as usize)
+                                    .style(Style::default().bg(theme.background).fg(theme.text))
+                                    .highlight_style(
+                                        Style::default()
+                                            .bg(theme.background)
+                                            .fg(theme.border)
+                                            .modifier(Modifier::Bold),
+                                    )
+                                    .render(term, &chunks[0]);
+
+                                match app.info_tab {
+                                    InfoTab::Room => {
+                                        let list_area = render_room_view(
+                                            term,
+                                            &chunks[1],
+                                            &game_info.current_room,
+                                            &game_info.adjacent_rooms,
+                                            selected_room,
+                                            app.focus == Focus::Rooms,
+                                            theme,
+                                        );
+                                        room_list_area.set(list_area);
+                                    }
+                                    InfoTab::Combat => render_combat_view(
+                                        term,
+                                        &chunks[1],
+                                        &game_info.combat,
+                                        theme,
+                                    ),
+                                    InfoTab::Map => render_map_view(
+                                        term,
+                                        &chunks[1],
+                                        &game_info.map,
+                                        game_info.current_room.number,
+                                        app.selected_map_line,
+                                        app.focus == Focus::Rooms,
+                                        theme,
+                                    ),
+                                }
+                            });
                     });
 
                 // BOTTOM SECTION
                 Group::default()
                     .direction(Direction::Horizontal)
-                    .sizes(&[Size::Percent(30), Size::Percent(40), Size::Percent(30)])
+                    .sizes(&[
+                        Size::Percent(app.panels.player),
+                        Size::Percent(app.panels.feed),
+                        Size::Percent(app.panels.entities),
+                    ])
                     .render(term, &chunks[2], |term, chunks| {
                         // PLAYER SECTION
                         {
@@ -152,7 +259,7 @@ impl TerminalInterface {
                                             .render(term, &chunks[0], |term, chunks| {
                                                 // Stat Listing
                                                 {
-                                                    let list_items = vec![
+                                                    let list_items = [
                                                         format!("Health: {}", player.health),
                                                         format!("Gold: {}", player.gold),
                                                         format!("Attack: {}", player.attack),
@@ -161,8 +268,8 @@ impl TerminalInterface {
                                                     ];
 
                                                     let stats_style = Style::default()
-                                                        .fg(STANDARD_TEXT_COLOR)
-                                                        .bg(BACKGROUND_COLOR);
+                                                        .fg(theme.text)
+                                                        .bg(theme.background);
 
                                                     let items = list_items.iter().map(|msg| {
                                                         Item::StyledData(msg, &stats_style)
@@ -173,35 +280,34 @@ impl TerminalInterface {
                                                             Block::default()
                                                                 .border_style(
                                                                     Style::default()
-                                                                        .bg(BACKGROUND_COLOR)
-                                                                        .fg(BORDER_COLOR),
+                                                                        .bg(theme.background)
+                                                                        .fg(theme.border),
                                                                 )
                                                                 .style(
                                                                     Style::default()
-                                                                        .bg(BACKGROUND_COLOR),
+                                                                        .bg(theme.background),
                                                                 )
                                                                 .title(player.name.as_str())
                                                                 .title_style(
                                                                     Style::default()
-                                                                        .bg(BACKGROUND_COLOR)
-                                                                        .fg(STANDARD_TEXT_COLOR)
+                                                                        .bg(theme.background)
+                                                                        .fg(theme.text)
                                                                         .modifier(
                                                                             Modifier::Underline,
                                                                         ),
                                                                 ),
                                                         )
                                                         .style(
-                                                            Style::default().bg(BACKGROUND_COLOR),
+                                                            Style::default().bg(theme.background),
                                                         )
                                                         .render(term, &chunks[0]);
                                                 }
 
                                                 // Stat Chart
                                                 {
-                                                    let mut point_sum = (player.attack
-                                                        + player.defense
-                                                        + player.regen)
-                                                        as f32;
+                                                    let mut point_sum = f32::from(player.attack)
+                                                        + f32::from(player.defense)
+                                                        + f32::from(player.regen);
 
                                                     if point_sum == 0f32 {
                                                         point_sum = 1f32;
@@ -229,56 +335,66 @@ impl TerminalInterface {
                                                             |term, chunks| {
                                                                 // Attack
                                                                 Gauge::default()
-                                                                    .label(&format!(
-                                                                        "Attack: {}/{}",
-                                                                        player.attack, point_sum
+                                                                    .label(&gauge_label(
+                                                                        format!(
+                                                                            "Attack: {}/{}",
+                                                                            player.attack, point_sum
+                                                                        ),
+                                                                        chunks[0].width,
                                                                     ))
                                                                     .style(
                                                                         Style::default()
-                                                                            .fg(Color::Red)
-                                                                            .bg(BACKGROUND_COLOR),
+                                                                            .fg(theme.danger)
+                                                                            .bg(theme.background),
                                                                     )
                                                                     .percent(attack_norm as u16)
                                                                     .render(term, &chunks[0]);
 
                                                                 // Defense
                                                                 Gauge::default()
-                                                                    .label(&format!(
-                                                                        "Defense: {}/{}",
-                                                                        player.defense, point_sum
+                                                                    .label(&gauge_label(
+                                                                        format!(
+                                                                            "Defense: {}/{}",
+                                                                            player.defense, point_sum
+                                                                        ),
+                                                                        chunks[1].width,
                                                                     ))
                                                                     .style(
                                                                         Style::default()
-                                                                            .fg(Color::Cyan)
-                                                                            .bg(BACKGROUND_COLOR),
+                                                                            .fg(theme.info)
+                                                                            .bg(theme.background),
                                                                     )
                                                                     .percent(defense_norm as u16)
                                                                     .render(term, &chunks[1]);
 
                                                                 // Regen
                                                                 Gauge::default()
-                                                                    .label(&format!(
-                                                                        "Regeneration: {}/{}",
-                                                                        player.regen, point_sum
+                                                                    .label(&gauge_label(
+                                                                        format!(
+                                                                            "Regeneration: {}/{}",
+                                                                            player.regen, point_sum
+                                                                        ),
+                                                                        chunks[2].width,
                                                                     ))
                                                                     .style(
                                                                         Style::default()
-                                                                            .fg(Color::LightGreen)
-                                                                            .bg(BACKGROUND_COLOR),
+                                                                            .fg(theme.good)
+                                                                            .bg(theme.background),
                                                                     )
                                                                     .percent(regen_norm as u16)
                                                                     .render(term, &chunks[2]);
 
                                                                 Paragraph::default()
+                                                                    .wrap(true)
                                                                     .text(&format!(
                                                                         "Total Points: {}",
                                                                         point_sum as u32
                                                                     ))
                                                                     .style(
                                                                         Style::default()
-                                                                            .bg(BACKGROUND_COLOR)
+                                                                            .bg(theme.background)
                                                                             .fg(
-                                                                                STANDARD_TEXT_COLOR,
+                                                                                theme.text,
                                                                             ),
                                                                     )
                                                                     .render(term, &chunks[3]);
@@ -293,8 +409,8 @@ impl TerminalInterface {
                                         Paragraph::default()
                                             .style(
                                                 Style::default()
-                                                    .bg(BACKGROUND_COLOR)
-                                                    .fg(STANDARD_TEXT_COLOR)
+                                                    .bg(theme.background)
+                                                    .fg(theme.text)
                                                     .modifier(Modifier::Italic),
                                             )
                                             .block(
@@ -302,10 +418,11 @@ impl TerminalInterface {
                                                     .borders(Borders::TOP)
                                                     .border_style(
                                                         Style::default()
-                                                            .bg(BACKGROUND_COLOR)
-                                                            .fg(BORDER_COLOR),
+                                                            .bg(theme.background)
+                                                            .fg(theme.border),
                                                     ),
                                             )
+                                            .wrap(true)
                                             .text(&player.description)
                                             .render(term, &chunks[1]);
                                     }
@@ -314,40 +431,29 @@ impl TerminalInterface {
 
                         // MESSAGE FEED
                         {
-                            let items = messages.iter().map(|msg| {
-                                Item::StyledData(
-                                    format!("{}: {}", msg.sender, msg.content),
-                                    &message_style,
-                                )
-                            });
-                            List::new(items)
-                                .block(
-                                    Block::default()
-                                        .border_style(
-                                            Style::default().bg(BACKGROUND_COLOR).fg(BORDER_COLOR),
-                                        )
-                                        .title("Message Feed")
-                                        .title_style(
-                                            Style::default()
-                                                .bg(BACKGROUND_COLOR)
-                                                .fg(STANDARD_TEXT_COLOR)
-                                                .modifier(Modifier::Underline),
-                                        )
-                                        .style(Style::default().bg(BACKGROUND_COLOR))
-                                        .borders(Borders::LEFT | Borders::RIGHT),
-                                )
-                                .style(Style::default().bg(BACKGROUND_COLOR))
-                                .render(term, &chunks[1]);
+                            let layout = render_message_feed(
+                                term,
+                                &chunks[1],
+                                &game_info,
+                                &app.feed_view,
+                                app.feed_scroll,
+                                &app.keys,
+                                theme,
+                            );
+                            feed_layout.set(layout);
                         }
 
                         // ENTITY VIEW SECTION
                         {
-                            Block::default()
-                                .border_style(
-                                    Style::default().bg(BACKGROUND_COLOR).fg(BORDER_COLOR),
-                                )
-                                .style(Style::default().bg(BACKGROUND_COLOR))
-                                .render(term, &chunks[2]);
+                            render_entity_view(
+                                term,
+                                &chunks[2],
+                                &entities,
+                                &game_info.peak_health,
+                                app.selected_entity,
+                                app.focus == Focus::Entities,
+                                theme,
+                            );
                         }
                     });
 
@@ -356,40 +462,917 @@ impl TerminalInterface {
                     .direction(Direction::Horizontal)
                     .sizes(&[Size::Percent(100)])
                     .render(term, &chunks[3], |term, chunks| {
-                        Block::default()
-                            .border_style(Style::default().bg(BACKGROUND_COLOR).fg(BORDER_COLOR))
-                            .style(Style::default().bg(BACKGROUND_COLOR))
-                            .borders(Borders::ALL)
-                            .render(term, &chunks[0]);
+                        let input_block = Block::default()
+                            .border_style(Style::default().bg(theme.background).fg(theme.border))
+                            .style(Style::default().bg(theme.background))
+                            .borders(Borders::ALL);
+                        render_input_line(term, &chunks[0], input_block, &app.input, true, theme);
                     });
             });
 
+        if let Some(notification) = game_info.notifications.showing(now) {
+            render_notification(&mut self.term, &self.size, notification, theme);
+        }
+
+        self.room_list_area = room_list_area.get();
+        self.feed_layout = feed_layout.get();
+        self.room_list_offset = list_offset(selected_room, self.room_list_area.height);
+
         self.term
             .draw()
-            .map_err(|_| String::from("Failed to render."))?;
+            .map_err(Error::terminal("Failed to render"))?;
         Ok(())
     }
 
-    pub fn show_cursor(&mut self) -> Result<(), ()> {
-        self.term.show_cursor().map_err(|_| ())
+    pub fn feed_layout(&self) -> FeedLayout {
+        self.feed_layout
+    }
+
+    /// Maps a mouse click, in termion's one-based coordinates, to the exit
+    /// drawn under it.
+    pub fn room_at(&self, x: u16, y: u16) -> Option<usize> {
+        let area = self.room_list_area;
+        let (x, y) = (x.saturating_sub(1), y.saturating_sub(1));
+        if x < area.x || x >= area.x + area.width || y < area.y || y >= area.y + area.height {
+            return None;
+        }
+        Some(self.room_list_offset + (y - area.y) as usize)
     }
 
-    pub fn update(&mut self) -> Result<(), String> {
+    pub fn show_cursor(&mut self) -> Result<()> {
+        self.term.show_cursor().map_err(Error::terminal("Failed to show cursor"))
+    }
+
+    pub fn update(&mut self) -> Result<()> {
         let size = self.term
             .size()
-            .map_err(|_| String::from("Failed to to see new terminal size in update."))?;
+            .map_err(Error::terminal("Failed to get new terminal size"))?;
         if size != self.size {
             self.term
                 .resize(size)
-                .map_err(|_| String::from("Failed to resize terminal."))?;
+                .map_err(Error::terminal("Failed to resize terminal"))?;
             self.size = size;
         }
         Ok(())
     }
 }
 
-impl Drop for TerminalInterface {
+fn render_input_line<B: Backend>(
+    term: &mut Terminal<B>,
+    area: &Rect,
+    block: Block,
+    line: &InputLine,
+    show_cursor: bool,
+    theme: &Theme,
+) {
+    let inner = block.inner(area);
+    let (visible, cursor_column) = line.visible(inner.width as usize);
+
+    Paragraph::default()
+        .block(block)
+        .style(Style::default().bg(theme.background).fg(theme.text))
+        .wrap(true)
+        .raw(true)
+        .text(&visible)
+        .render(term, area);
+
+    // The real cursor stays hidden, so draw our own.
+    if show_cursor && inner.width > 0 && inner.height > 0 {
+        Cursor.render(term, &Rect::new(inner.x + cursor_column as u16, inner.y, 1, 1));
+    }
+}
+
+/// Resets every cell of the area it's rendered to.
+struct Clear;
+
+impl Widget for Clear {
+    fn draw(&mut self, area: &Rect, buf: &mut Buffer) {
+        for y in area.y..area.y + area.height {
+            for x in area.x..area.x + area.width {
+                buf.get_mut(x, y).reset();
+            }
+        }
+    }
+}
+
+/// Inverts the cell at the top left of the area it's rendered to.
+struct Cursor;
+
+impl Widget for Cursor {
+    fn draw(&mut self, area: &Rect, buf: &mut Buffer) {
+        buf.get_mut(area.x, area.y).set_modifier(Modifier::Invert);
+    }
+}
+
+fn field_block<'a>(title: &'a str, focused: bool, theme: &Theme) -> Block<'a> {
+    let border_color = if focused {
+        theme.text
+    } else {
+        theme.border
+    };
+
+    Block::default()
+        .borders(Borders::ALL)
+        .title(title)
+        .title_style(Style::default().bg(theme.background).fg(theme.text))
+        .border_style(Style::default().bg(theme.background).fg(border_color))
+        .style(Style::default().bg(theme.background))
+}
+
+/// The color the top bar shows the connection status in.
+fn status_color(status: &Status, theme: &Theme) -> Color {
+    match *status {
+        Status::Offline => theme.muted,
+        Status::Connected(_) => theme.good,
+        Status::Reconnecting { .. } => theme.danger,
+        Status::Replaying { finished: true, .. } => theme.muted,
+        Status::Replaying { .. } => theme.info,
+    }
+}
+
+// Widest a notification gets, narrower screens get one their own width.
+const NOTIFICATION_WIDTH: u16 = 60;
+
+/// Draws an ERROR from the server over the top right corner of the screen.
+fn render_notification<B: Backend>(
+    term: &mut Terminal<B>,
+    screen: &Rect,
+    notification: &Notification,
+    theme: &Theme,
+) {
+    let width = NOTIFICATION_WIDTH.min(screen.width.saturating_sub(4));
+    if width < 3 {
+        return;
+    }
+
+    let message_style = Style::default().bg(theme.background).fg(theme.danger);
+    let text_style = Style::default().bg(theme.background).fg(theme.text);
+    let command_style = Style::default().bg(theme.background).fg(theme.muted);
+    let suggestion_style = Style::default().bg(theme.background).fg(theme.info);
+
+    let code = notification.code;
+    let mut text = vec![
+        (notification.message.clone(), &message_style),
+        (String::from(code.explanation()), &text_style),
+    ];
+    if let Some(ref command) = notification.command {
+        text.push((format!("After: {}", command), &command_style));
+    }
+    text.push((String::from(code.suggestion()), &suggestion_style));
+
+    let lines: Vec<(String, &Style)> = text.into_iter()
+        .flat_map(|(text, style)| {
+            wrap_text(&text, width as usize - 2)
+                .into_iter()
+                .map(move |line| (line, style))
+        })
+        .collect();
+
+    let height = (lines.len() as u16 + 2).min(screen.height.saturating_sub(2));
+    let area = Rect::new(screen.x + screen.width - width - 2, screen.y + 1, width, height);
+
+    // Nothing drawn underneath should show through.
+    Clear.render(term, &area);
+
+    List::new(
+        lines
+            .into_iter()
+            .map(|(line, style)| Item::StyledData(line, style)),
+    ).block(
+        Block::default()
+            .borders(Borders::ALL)
+            .title(code.title())
+            .title_style(Style::default().bg(theme.background).fg(theme.danger))
+            .border_style(Style::default().bg(theme.background).fg(theme.danger))
+            .style(Style::default().bg(theme.background)),
+    )
+        .style(Style::default().bg(theme.background))
+        .render(term, &area);
+}
+
+/// Breaks `text` into lines no wider than `width`, splitting words that are
+/// too long to fit on a line of their own.
+fn wrap_text(text: &str, width: usize) -> Vec<String> {
+    let mut lines = Vec::new();
+    let mut line = String::new();
+    let mut line_width = 0;
+
+    if width == 0 {
+        return lines;
+    }
+
+    for word in text.split_whitespace() {
+        let mut word: Vec<char> = word.chars().collect();
+
+        if line_width > 0 && line_width + 1 + word.len() > width {
+            lines.push(line.split_off(0));
+            line_width = 0;
+        }
+
+        while word.len() > width {
+            let rest = word.split_off(width);
+            if line_width > 0 {
+                lines.push(line.split_off(0));
+                line_width = 0;
+            }
+            lines.push(word.into_iter().collect());
+            word = rest;
+        }
+
+        if line_width > 0 {
+            line.push(' ');
+            line_width += 1;
+        }
+        line_width += word.len();
+        line.extend(word);
+    }
+
+    if line_width > 0 || lines.is_empty() {
+        lines.push(line);
+    }
+    lines
+}
+
+fn render_message_feed<B: Backend>(
+    term: &mut Terminal<B>,
+    area: &Rect,
+    game_info: &GameInformation,
+    view: &FeedView,
+    scroll: usize,
+    keys: &KeyBindings,
+    theme: &Theme,
+) -> FeedLayout {
+    let message_style = Style::default().bg(theme.background).fg(theme.text);
+    let whisper_style = message_style.fg(theme.whisper);
+    let indicator_style = message_style.modifier(Modifier::Invert);
+
+    let title = match *view {
+        FeedView::All => String::from("Message Feed"),
+        FeedView::Conversation(ref name) => format!("Conversation with {} (/dm to return)", name),
+    };
+
+    let block = Block::default()
+        .border_style(Style::default().bg(theme.background).fg(theme.border))
+        .title(&title)
+        .title_style(
+            Style::default()
+                .bg(theme.background)
+                .fg(theme.text)
+                .modifier(Modifier::Underline),
+        )
+        .style(Style::default().bg(theme.background))
+        .borders(Borders::LEFT | Borders::RIGHT);
+    let inner = block.inner(area);
+    let height = inner.height as usize;
+
+    let player_name = &game_info.player.name;
+    let is_private = |msg: &Message| {
+        game_info.is_whisper(msg)
+            || (!player_name.is_empty() && msg.sender == *player_name && !msg.receiver.is_empty())
+    };
+
+    let lines: Vec<(String, &Style)> = game_info
+        .messages
+        .iter()
+        .filter(|msg| match *view {
+            FeedView::All => true,
+            FeedView::Conversation(ref name) => {
+                is_private(msg) && (msg.sender == *name || msg.receiver == *name)
+            }
+        })
+        .flat_map(|msg| {
+            let (text, style) = if !is_private(msg) {
+                (format!("{}: {}", msg.sender, msg.content), &message_style)
+            } else if msg.sender == *player_name {
+                (format!("You -> {}: {}", msg.receiver, msg.content), &whisper_style)
+            } else {
+                (format!("{} whispers: {}", msg.sender, msg.content), &whisper_style)
+            };
+            let text = format!("[{}] {}", msg.received.format("%H:%M:%S"), text);
+            wrap_text(&text, inner.width as usize)
+                .into_iter()
+                .map(move |line| (line, style))
+        })
+        .collect();
+
+    // Scrolling counts lines back from the newest message.
+    let max_scroll = lines.len().saturating_sub(height);
+    let scroll = scroll.min(max_scroll);
+    let end = lines.len() - scroll;
+    let start = end.saturating_sub(height);
+    let mut visible: Vec<(String, &Style)> = lines[start..end].to_vec();
+
+    if scroll > 0 && !visible.is_empty() {
+        let last = visible.len() - 1;
+        visible[last] = (
+            format!("-- {} more below ({}) --", scroll, key_name(keys.scroll_down)),
+            &indicator_style,
+        );
+    }
+
+    List::new(
+        visible
+            .into_iter()
+            .map(|(line, style)| Item::StyledData(line, style)),
+    ).block(block)
+        .style(Style::default().bg(theme.background))
+        .render(term, area);
+
+    FeedLayout {
+        page_height: height,
+        max_scroll,
+    }
+}
+
+fn render_combat_view<B: Backend>(
+    term: &mut Terminal<B>,
+    area: &Rect,
+    combat: &CombatLog,
+    theme: &Theme,
+) {
+    let text_style = Style::default().bg(theme.background).fg(theme.text);
+    let damage_style = text_style.fg(theme.danger);
+    let regen_style = text_style.fg(theme.good);
+    let gold_style = text_style.fg(theme.gold);
+
+    Group::default()
+        .direction(Direction::Horizontal)
+        .sizes(&[Size::Percent(30), Size::Percent(70)])
+        .render(term, area, |term, chunks| {
+            let totals = &combat.totals;
+            Paragraph::default()
+                .block(field_block("Session Totals", false, theme))
+                .style(text_style)
+                .wrap(true)
+                .raw(true)
+                .text(&format!(
+                    "Fights: {}{}\nDamage dealt: {}\nDamage taken: {}\nRegenerated: {}\n\
+                     Kills: {}\nDeaths: {}\nGold gained: {}\nGold lost: {}",
+                    totals.fights,
+                    if combat.in_fight() { " (fighting)" } else { "" },
+                    totals.damage_dealt,
+                    totals.damage_taken,
+                    totals.regenerated,
+                    totals.kills,
+                    totals.deaths,
+                    totals.gold_gained,
+                    totals.gold_lost
+                ))
+                .render(term, &chunks[0]);
+
+            let log_block = field_block("Combat Log", false, theme);
+            let height = log_block.inner(&chunks[1]).height as usize;
+            let skip = combat.entries.len().saturating_sub(height);
+
+            let items = combat.entries.iter().skip(skip).map(|entry| {
+                let (text, style) = match entry.event {
+                    CombatEvent::Damage { ref name, amount } => {
+                        (format!("{} took {} damage", name, amount), &damage_style)
+                    }
+                    CombatEvent::Regen { ref name, amount } => {
+                        (format!("{} regained {} health", name, amount), &regen_style)
+                    }
+                    CombatEvent::Death { ref name } => (format!("{} died", name), &damage_style),
+                    CombatEvent::Revived { ref name } => {
+                        (format!("{} is alive again", name), &regen_style)
+                    }
+                    CombatEvent::Gold { ref name, amount } if amount > 0 => {
+                        (format!("{} gained {} gold", name, amount), &gold_style)
+                    }
+                    CombatEvent::Gold { ref name, amount } => {
+                        (format!("{} lost {} gold", name, -amount), &gold_style)
+                    }
+                };
+                Item::StyledData(format!("[{}] {}", entry.time.format("%H:%M:%S"), text), style)
+            });
+
+            List::new(items)
+                .block(log_block)
+                .style(Style::default().bg(theme.background))
+                .render(term, &chunks[1]);
+        });
+}
+
+fn render_map_view<B: Backend>(
+    term: &mut Terminal<B>,
+    area: &Rect,
+    map: &RoomMap,
+    current: u16,
+    selected: usize,
+    focused: bool,
+    theme: &Theme,
+) {
+    let text_style = Style::default().bg(theme.background).fg(theme.text);
+    let current_style = text_style.fg(theme.border).modifier(Modifier::Bold);
+    let unexplored_style = text_style.fg(theme.muted);
+
+    let lines = map.tree(current);
+    let selected = selected.min(lines.len().saturating_sub(1));
+
+    Group::default()
+        .direction(Direction::Horizontal)
+        .sizes(&[Size::Percent(60), Size::Percent(40)])
+        .render(term, area, |term, chunks| {
+            let mut map_block = field_block("Discovered Rooms", focused, theme);
+            let inner = map_block.inner(&chunks[0]);
+            map_block.render(term, &chunks[0]);
+
+            if lines.is_empty() {
+                Paragraph::default()
+                    .style(text_style.modifier(Modifier::Italic))
+                    .wrap(true)
+                    .raw(true)
+                    .text("Nothing explored yet.")
+                    .render(term, &inner);
+            }
+
+            let offset = list_offset(selected, inner.height);
+            let visible = lines.iter().enumerate().skip(offset).take(inner.height as usize);
+            for (row, line) in visible {
+                let room = match map.rooms.get(&line.number) {
+                    Some(room) => room,
+                    None => continue,
+                };
+
+                let marker = if line.repeat {
+                    "<> "
+                } else if line.number == current {
+                    "@ "
+                } else if room.visited {
+                    "+ "
+                } else {
+                    "? "
+                };
+                let mut style = if line.number == current {
+                    current_style
+                } else if room.visited {
+                    text_style
+                } else {
+                    unexplored_style
+                };
+                if row == selected && focused {
+                    style = style.modifier(Modifier::Invert);
+                }
+
+                Paragraph::default()
+                    .style(style)
+                    .wrap(true)
+                    .raw(true)
+                    .text(&format!(
+                        "{}{}#{} {}",
+                        "  ".repeat(line.depth),
+                        marker,
+                        line.number,
+                        room.room.name
+                    ))
+                    .render(
+                        term,
+                        &Rect::new(inner.x, inner.y + (row - offset) as u16, inner.width, 1),
+                    );
+            }
+
+            let details = match lines.get(selected).and_then(|l| map.rooms.get(&l.number)) {
+                Some(room) => {
+                    let exits: Vec<String> = map.exits(room.room.number)
+                        .iter()
+                        .map(|n| format!("#{}", n))
+                        .collect();
+                    format!(
+                        "#{} {}{}\n\n{}\n\nExits: {}\n\n@ here  + visited  ? unexplored  <> another way in",
+                        room.room.number,
+                        room.room.name,
+                        if room.visited { "" } else { " (unexplored)" },
+                        room.room.description,
+                        if exits.is_empty() {
+                            String::from("unknown")
+                        } else {
+                            exits.join(", ")
+                        }
+                    )
+                }
+                None => String::new(),
+            };
+            Paragraph::default()
+                .block(field_block("Room Details", false, theme))
+                .style(text_style)
+                .wrap(true)
+                .raw(true)
+                .text(&details)
+                .render(term, &chunks[1]);
+        });
+}
+
+/// Cuts `label` down to what fits in a gauge `width` cells wide, which tui
+/// doesn't do itself.
+fn gauge_label(label: String, width: u16) -> String {
+    label.chars().take(width as usize).collect()
+}
+
+/// How far a list of `height` rows has to scroll to keep `selected` in view.
+fn list_offset(selected: usize, height: u16) -> usize {
+    (selected + 1).saturating_sub(height as usize)
+}
+
+/// Draws the current room and its exits, returning the area the exits were
+/// listed in so clicks can be mapped back onto them.
+fn render_room_view<B: Backend>(
+    term: &mut Terminal<B>,
+    area: &Rect,
+    room: &Room,
+    exits: &[Room],
+    selected: usize,
+    focused: bool,
+    theme: &Theme,
+) -> Rect {
+    let text_style = Style::default().bg(theme.background).fg(theme.text);
+    let list_area = Cell::new(Rect::default());
+
+    Group::default()
+        .direction(Direction::Horizontal)
+        .sizes(&[Size::Percent(65), Size::Percent(35)])
+        .render(term, area, |term, chunks| {
+            let title = if room.name.is_empty() {
+                String::from("Nowhere yet")
+            } else {
+                format!("#{} {}", room.number, room.name)
+            };
+            Paragraph::default()
+                .block(field_block(&title, false, theme))
+                .style(text_style)
+                .wrap(true)
+                .raw(true)
+                .text(&room.description)
+                .render(term, &chunks[0]);
+
+            let mut exits_block = field_block("Exits", focused, theme);
+            let inner = exits_block.inner(&chunks[1]);
+            exits_block.render(term, &chunks[1]);
+            list_area.set(inner);
+
+            if exits.is_empty() {
+                Paragraph::default()
+                    .style(text_style.modifier(Modifier::Italic))
+                    .wrap(true)
+                    .raw(true)
+                    .text("No known exits.")
+                    .render(term, &inner);
+            }
+
+            let offset = list_offset(selected, inner.height);
+            for (row, exit) in exits.iter().enumerate().skip(offset).take(inner.height as usize) {
+                let style = if row == selected && focused {
+                    text_style.modifier(Modifier::Invert)
+                } else {
+                    text_style
+                };
+                Paragraph::default()
+                    .style(style)
+                    .wrap(true)
+                    .raw(true)
+                    .text(&format!(
+                        "{} #{} {}",
+                        if row == selected { ">" } else { " " },
+                        exit.number,
+                        exit.name
+                    ))
+                    .render(
+                        term,
+                        &Rect::new(inner.x, inner.y + (row - offset) as u16, inner.width, 1),
+                    );
+            }
+        });
+
+    list_area.get()
+}
+
+fn render_entity_view<B: Backend>(
+    term: &mut Terminal<B>,
+    area: &Rect,
+    entities: &[Entity],
+    peak_health: &HashMap<String, i16>,
+    selected: usize,
+    focused: bool,
+    theme: &Theme,
+) {
+    let text_style = Style::default().bg(theme.background).fg(theme.text);
+    let selected = selected.min(entities.len().saturating_sub(1));
+
+    Group::default()
+        .direction(Direction::Vertical)
+        .sizes(&[Size::Percent(60), Size::Percent(40)])
+        .render(term, area, |term, chunks| {
+            let mut list_block = field_block("Room Occupants", focused, theme);
+            let inner = list_block.inner(&chunks[0]);
+            list_block.render(term, &chunks[0]);
+
+            if entities.is_empty() {
+                Paragraph::default()
+                    .style(text_style.modifier(Modifier::Italic))
+                    .wrap(true)
+                    .raw(true)
+                    .text("Nobody else is here.")
+                    .render(term, &inner);
+            }
+
+            let rows = inner.height as usize;
+            let offset = list_offset(selected, inner.height);
+
+            for (row, entity) in entities.iter().enumerate().skip(offset).take(rows) {
+                let y = inner.y + (row - offset) as u16;
+                let name_width = inner.width * 3 / 5;
+                let name_area = Rect::new(inner.x, y, name_width, 1);
+                let gauge_area = Rect::new(inner.x + name_width, y, inner.width - name_width, 1);
+
+                let mut style = if entity.is_alive {
+                    text_style
+                } else {
+                    text_style.fg(theme.muted)
+                };
+                if row == selected {
+                    style = style.modifier(Modifier::Invert);
+                }
+
+                Paragraph::default()
+                    .style(style)
+                    .wrap(true)
+                    .raw(true)
+                    .text(&format!(
+                        "{} {}{} {}g",
+                        if entity.is_monster { "[M]" } else { "[P]" },
+                        entity.name,
+                        if entity.is_alive { "" } else { " (dead)" },
+                        entity.gold
+                    ))
+                    .render(term, &name_area);
+
+                let peak = peak_health
+                    .get(&entity.name)
+                    .cloned()
+                    .unwrap_or(entity.health)
+                    .max(1);
+                let percent = (i32::from(entity.health.max(0)) * 100 / i32::from(peak)) as u16;
+                Gauge::default()
+                    .style(Style::default().fg(theme.danger).bg(theme.background))
+                    .label(&gauge_label(format!("{} HP", entity.health), gauge_area.width))
+                    .percent(percent.min(100))
+                    .render(term, &gauge_area);
+            }
+
+            // Details of the highlighted entity
+            let details = match entities.get(selected) {
+                Some(entity) => format!(
+                    "{} - {}, {}\nAttack {}  Defense {}  Regen {}\nHealth {}  Gold {}\n\n{}",
+                    entity.name,
+                    if entity.is_monster { "Monster" } else { "Player" },
+                    if entity.is_alive { "alive" } else { "dead" },
+                    entity.attack,
+                    entity.defense,
+                    entity.regen,
+                    entity.health,
+                    entity.gold,
+                    entity.description
+                ),
+                None => String::new(),
+            };
+            Paragraph::default()
+                .block(field_block("Details", false, theme))
+                .style(text_style)
+                .wrap(true)
+                .raw(true)
+                .text(&details)
+                .render(term, &chunks[1]);
+        });
+}
+
+fn render_character_form<B: Backend>(
+    term: &mut Terminal<B>,
+    area: &Rect,
+    form: &CharacterForm,
+    game: &Game,
+    theme: &Theme,
+) {
+    let text_style = Style::default().bg(theme.background).fg(theme.text);
+    let spent = form.points_spent();
+    let over_budget = spent > u32::from(game.init_points) || spent > u32::from(game.stat_limit);
+
+    Group::default()
+        .direction(Direction::Vertical)
+        .margin(2)
+        .sizes(&[
+            Size::Percent(25),
+            Size::Fixed(3),
+            Size::Fixed(3),
+            Size::Fixed(3),
+            Size::Fixed(3),
+            Size::Fixed(1),
+            Size::Fixed(1),
+            Size::Fixed(2),
+            Size::Min(0),
+        ])
+        .render(term, area, |term, chunks| {
+            Paragraph::default()
+                .block(field_block("Create Your Character", false, theme))
+                .style(text_style)
+                .wrap(true)
+                .raw(true)
+                .text(&game.description)
+                .render(term, &chunks[0]);
+
+            render_input_line(
+                term,
+                &chunks[1],
+                field_block("Name", form.focus == Field::Name, theme),
+                &form.name,
+                form.focus == Field::Name,
+                theme,
+            );
+            render_input_line(
+                term,
+                &chunks[2],
+                field_block("Description", form.focus == Field::Description, theme),
+                &form.description,
+                form.focus == Field::Description,
+                theme,
+            );
+
+            Group::default()
+                .direction(Direction::Horizontal)
+                .sizes(&[Size::Percent(33), Size::Percent(33), Size::Percent(34)])
+                .render(term, &chunks[3], |term, chunks| {
+                    let stats = [
+                        ("Attack", form.attack, Field::Attack),
+                        ("Defense", form.defense, Field::Defense),
+                        ("Regeneration", form.regen, Field::Regen),
+                    ];
+                    for (i, &(title, value, field)) in stats.iter().enumerate() {
+                        Paragraph::default()
+                            .block(field_block(title, form.focus == field, theme))
+                            .style(text_style)
+                            .wrap(true)
+                            .raw(true)
+                            .text(&format!("< {} >", value))
+                            .render(term, &chunks[i]);
+                    }
+                });
+
+            let budget = u32::from(game.init_points).max(1);
+            Gauge::default()
+                .block(field_block("Points", false, theme))
+                .style(Style::default()
+                    .fg(if over_budget { theme.danger } else { theme.border })
+                    .bg(theme.background))
+                .label(&gauge_label(
+                    format!("{}/{} spent, stat limit {}", spent, game.init_points, game.stat_limit),
+                    chunks[4].width.saturating_sub(2),
+                ))
+                .percent((spent.min(budget) * 100 / budget) as u16)
+                .render(term, &chunks[4]);
+
+            let checkbox = if form.join_battle { "[x]" } else { "[ ]" };
+            Paragraph::default()
+                .style(if form.focus == Field::JoinBattle {
+                    text_style.modifier(Modifier::Invert)
+                } else {
+                    text_style
+                })
+                .wrap(true)
+                .raw(true)
+                .text(&format!("{} Join battles automatically", checkbox))
+                .render(term, &chunks[5]);
+
+            Paragraph::default()
+                .style(if form.focus == Field::Submit {
+                    text_style.modifier(Modifier::Invert)
+                } else {
+                    text_style
+                })
+                .wrap(true)
+                .raw(true)
+                .text("[ Create Character ]")
+                .render(term, &chunks[6]);
+
+            let status = match form.status {
+                FormStatus::Editing => String::from(
+                    "Tab/arrows to move between fields, left/right to adjust stats, Enter to continue.",
+                ),
+                FormStatus::Waiting(_) => String::from("Waiting for the server..."),
+                FormStatus::Rejected(ref reason) => format!("Rejected: {}", reason),
+            };
+            Paragraph::default()
+                .style(match form.status {
+                    FormStatus::Rejected(_) => Style::default().bg(theme.background).fg(theme.danger),
+                    _ => text_style,
+                })
+                .wrap(true)
+                .raw(true)
+                .text(&status)
+                .render(term, &chunks[7]);
+        });
+}
+
+fn set_bracketed_paste(enabled: bool) -> io::Result<()> {
+    let mut stdout = io::stdout();
+    if enabled {
+        write!(stdout, "{}", ENABLE_BRACKETED_PASTE)?;
+    } else {
+        write!(stdout, "{}", DISABLE_BRACKETED_PASTE)?;
+    }
+    stdout.flush()
+}
+
+impl<B: Backend> Drop for TerminalInterface<B> {
     fn drop(&mut self) {
-        self.show_cursor().expect("Failed to reshow cursor.");
+        // Errors are ignored, panicking here while unwinding would abort.
+        if self.bracketed_paste {
+            let _ = set_bracketed_paste(false);
+        }
+        let _ = self.show_cursor();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::env;
+    use std::fs;
+    use std::path::PathBuf;
+    use std::sync::*;
+
+    use chrono::prelude::*;
+
+    use app::{App, Screen};
+    use headless::BufferBackend;
+    use super::TerminalInterface;
+
+    // Screens are compared against the text files in `tests/snapshots`. Set
+    // UPDATE_SNAPSHOTS to record them again after an intended change to the
+    // layout, a missing snapshot is a failure otherwise.
+
+    fn fixed_time() -> DateTime<Local> {
+        let naive = NaiveDate::from_ymd_opt(2018, 3, 14)
+            .and_then(|date| date.and_hms_opt(13, 37, 0))
+            .unwrap();
+        Local.from_local_datetime(&naive).unwrap()
+    }
+
+    fn render_mock(width: u16, height: u16) -> String {
+        let mut game_info = ::mock_game_information();
+        for message in &mut game_info.messages {
+            message.received = fixed_time();
+        }
+
+        let game_data = Arc::new(Mutex::new(game_info));
+        let backend = BufferBackend::new(width, height);
+        let mut interface = TerminalInterface::with_backend(backend, game_data).unwrap();
+        interface.render_at(&App::new(Screen::Main), fixed_time()).unwrap();
+        interface.term.backend().contents()
+    }
+
+    fn assert_snapshot(name: &str, actual: &str) {
+        let path = PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+            .join("tests")
+            .join("snapshots")
+            .join(format!("{}.txt", name));
+
+        if env::var_os("UPDATE_SNAPSHOTS").is_some() {
+            fs::create_dir_all(path.parent().unwrap()).unwrap();
+            fs::write(&path, actual).unwrap();
+            return;
+        }
+
+        let expected = fs::read_to_string(&path).unwrap_or_else(|e| {
+            panic!(
+                "Failed to read {} ({}), run with UPDATE_SNAPSHOTS=1 to record it. It renders as:\n{}",
+                path.display(),
+                e,
+                actual
+            )
+        });
+        assert!(
+            expected == actual,
+            "{} no longer matches {}, it now renders as:\n{}",
+            name,
+            path.display(),
+            actual
+        );
+    }
+
+    #[test]
+    fn main_screen_80x24() {
+        assert_snapshot("main_80x24", &render_mock(80, 24));
+    }
+
+    #[test]
+    fn main_screen_120x40() {
+        assert_snapshot("main_120x40", &render_mock(120, 40));
+    }
+
+    #[test]
+    fn main_screen_200x60() {
+        assert_snapshot("main_200x60", &render_mock(200, 60));
+    }
+
+    #[test]
+    fn main_screen_40x12() {
+        assert_snapshot("main_40x12", &render_mock(40, 12));
     }
 }